@@ -0,0 +1,78 @@
+//! Per-frame cost of getting a decoded frame into a texture.
+//!
+//! - `cpu`: the current path, building a new `Image` through `image`.
+//! - `in_place`: copying into an existing `Image`, as dirty-region uploads
+//!   and texture reuse do.
+//!
+//! Run with `cargo bench --features bench`.
+
+extern crate gstreamer_video as gst_video;
+
+use bevy::{
+    prelude::Image,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+use bevy_gst_video::{
+    hdr::{HdrMode, VideoTransfer},
+    plugin::frame_to_image,
+    video::VideoInfo,
+};
+use criterion::{
+    black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput,
+};
+
+const SIZES: [(&str, u32, u32); 3] = [
+    ("720p", 1280, 720),
+    ("1080p", 1920, 1080),
+    ("4k", 3840, 2160),
+];
+
+fn rgba_frame(width: u32, height: u32) -> VideoInfo {
+    VideoInfo {
+        width,
+        height,
+        data: vec![128; (width * height * 4) as usize],
+        pts: 0,
+        has_alpha: false,
+        format: gst_video::VideoFormat::Rgba,
+        transfer: VideoTransfer::Sdr,
+    }
+}
+
+fn conversion(c: &mut Criterion) {
+    let mut group = c.benchmark_group("frame_upload");
+    for (name, width, height) in SIZES {
+        group.throughput(Throughput::Elements(1));
+        let frame = rgba_frame(width, height);
+
+        group.bench_function(BenchmarkId::new("cpu", name), |b| {
+            b.iter_batched(
+                || rgba_frame(width, height),
+                |frame| frame_to_image(frame, HdrMode::Tonemap),
+                BatchSize::LargeInput,
+            )
+        });
+
+        let mut target = Image::new_fill(
+            Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[0, 0, 0, 255],
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        );
+        group.bench_with_input(BenchmarkId::new("in_place", name), &frame, |b, frame| {
+            b.iter(|| target.data.copy_from_slice(black_box(&frame.data)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, conversion);
+criterion_main!(benches);
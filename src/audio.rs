@@ -0,0 +1,223 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "audio")]
+use bevy::audio::{Decodable, Source};
+use bevy::prelude::*;
+
+/// Layout video audio is converted to for the `bevy_audio` backend.
+pub const STREAM_CHANNELS: u16 = 2;
+pub const STREAM_RATE: u32 = 48_000;
+
+/// Equalizer and filter settings for a player's audio, e.g. to muffle a
+/// cutscene underwater or make it sound like a radio.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Reflect)]
+pub struct AudioEffects {
+    /// Gain in dB (-24 to 12) of the ten bands, from 29Hz to 15kHz.
+    pub equalizer: [f64; 10],
+    /// Cutoff in Hz above which audio is removed.
+    pub low_pass: Option<f32>,
+    /// Cutoff in Hz below which audio is removed.
+    pub high_pass: Option<f32>,
+}
+
+impl AudioEffects {
+    pub fn underwater() -> Self {
+        AudioEffects {
+            low_pass: Some(500.0),
+            ..Default::default()
+        }
+    }
+
+    pub fn radio() -> Self {
+        AudioEffects {
+            low_pass: Some(3400.0),
+            high_pass: Some(300.0),
+            ..Default::default()
+        }
+    }
+}
+
+/// Temporary gain on top of the volume, so dialogue can lower video audio
+/// and bring it back without a jump. Evaluated once per audio buffer.
+#[derive(Debug, Clone, Copy)]
+pub struct Ducking {
+    from: f32,
+    to: f32,
+    started: Instant,
+    fade: Duration,
+}
+
+impl Default for Ducking {
+    fn default() -> Self {
+        Ducking {
+            from: 1.0,
+            to: 1.0,
+            started: Instant::now(),
+            fade: Duration::ZERO,
+        }
+    }
+}
+
+impl Ducking {
+    /// Gain at `now`, fading linearly from the previous target.
+    pub fn gain(&self, now: Instant) -> f32 {
+        let elapsed = now.saturating_duration_since(self.started);
+        if elapsed >= self.fade {
+            return self.to;
+        }
+        let progress = elapsed.as_secs_f32() / self.fade.as_secs_f32();
+        self.from + (self.to - self.from) * progress
+    }
+
+    /// Starts fading from the current gain to `to`.
+    pub(crate) fn fade_to(&mut self, to: f32, fade: Duration) {
+        let now = Instant::now();
+        self.from = self.gain(now);
+        self.to = to;
+        self.started = now;
+        self.fade = fade;
+    }
+
+    pub fn is_ducked(&self) -> bool {
+        self.to < 1.0
+    }
+
+    /// Duration of the last fade, reused when unducking.
+    pub fn fade(&self) -> Duration {
+        self.fade
+    }
+}
+
+/// Samples of 10ms of audio, played as silence while the pipeline has not
+/// delivered anything.
+#[cfg(feature = "audio")]
+const CHUNK: usize = (STREAM_RATE / 100) as usize * STREAM_CHANNELS as usize;
+
+/// Decoded video audio handed to `bevy_audio`, so it mixes with game sound
+/// and follows `GlobalVolume`. Samples are interleaved stereo `f32` at
+/// 48kHz; the stream plays silence while it runs dry and ends once closed.
+#[derive(Asset, TypePath, Clone)]
+pub struct VideoAudioStream {
+    samples: Arc<Mutex<VecDeque<f32>>>,
+    #[cfg_attr(not(feature = "audio"), allow(dead_code))]
+    volume: Arc<Mutex<f32>>,
+    closed: Arc<AtomicBool>,
+}
+
+impl VideoAudioStream {
+    pub(crate) fn new(volume: Arc<Mutex<f32>>) -> Self {
+        VideoAudioStream {
+            samples: Arc::new(Mutex::new(VecDeque::new())),
+            volume,
+            closed: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    #[cfg_attr(not(feature = "audio"), allow(dead_code))]
+    pub(crate) fn push(&self, samples: &[f32]) {
+        if let Ok(mut queue) = self.samples.lock() {
+            queue.extend(samples);
+        }
+    }
+
+    /// Drops samples not played yet, e.g. on pause.
+    pub(crate) fn clear(&self) {
+        if let Ok(mut queue) = self.samples.lock() {
+            queue.clear();
+        }
+    }
+
+    /// Ends the stream; `bevy_audio` finishes the sink once it is drained.
+    pub(crate) fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+        self.clear();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    /// Samples waiting to be played.
+    pub fn buffered(&self) -> usize {
+        self.samples.lock().map(|queue| queue.len()).unwrap_or(0)
+    }
+}
+
+/// Pulls samples from a `VideoAudioStream` in chunks, so the lock is taken
+/// once per 10ms instead of once per sample.
+#[cfg(feature = "audio")]
+pub struct VideoAudioDecoder {
+    stream: VideoAudioStream,
+    chunk: VecDeque<f32>,
+}
+
+#[cfg(feature = "audio")]
+impl VideoAudioDecoder {
+    fn refill(&mut self) {
+        let volume = self.stream.volume.lock().map(|v| *v).unwrap_or(1.0);
+        if let Ok(mut queue) = self.stream.samples.lock() {
+            let available = queue.len().min(CHUNK);
+            // Whole frames only, so the channels stay aligned.
+            let take = available - available % STREAM_CHANNELS as usize;
+            self.chunk
+                .extend(queue.drain(..take).map(|sample| sample * volume));
+        }
+        if self.chunk.is_empty() {
+            self.chunk.resize(CHUNK, 0.0);
+        }
+    }
+}
+
+#[cfg(feature = "audio")]
+impl Iterator for VideoAudioDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.stream.is_closed() {
+            return None;
+        }
+        if self.chunk.is_empty() {
+            self.refill();
+        }
+        self.chunk.pop_front()
+    }
+}
+
+#[cfg(feature = "audio")]
+impl Source for VideoAudioDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        STREAM_CHANNELS
+    }
+
+    fn sample_rate(&self) -> u32 {
+        STREAM_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+#[cfg(feature = "audio")]
+impl Decodable for VideoAudioStream {
+    type DecoderItem = f32;
+    type Decoder = VideoAudioDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        VideoAudioDecoder {
+            stream: self.clone(),
+            chunk: VecDeque::new(),
+        }
+    }
+}
@@ -0,0 +1,42 @@
+extern crate gstreamer as gst;
+
+use std::str::FromStr;
+
+use crate::init::ensure_gstreamer;
+
+/// What the GStreamer installation on this machine can handle. Queries
+/// initialize GStreamer with the default options if `VideoPlugin` hasn't
+/// yet.
+pub struct VideoBackend;
+
+impl VideoBackend {
+    /// Whether a stream of `mime_or_caps` would be decoded, e.g.
+    /// `"video/x-h265"`, `"video/x-av1"` or full caps such as
+    /// `"video/x-h264, profile=(string)high"`. Containers such as
+    /// `"video/quicktime"` only need a demuxer; their streams are checked
+    /// separately. Unparsable caps can't be played.
+    pub fn can_play(mime_or_caps: &str) -> bool {
+        if ensure_gstreamer().is_err() {
+            return false;
+        }
+        let Ok(caps) = gst::Caps::from_str(mime_or_caps) else {
+            return false;
+        };
+        if caps.is_empty() {
+            return false;
+        }
+        let raw = caps
+            .iter()
+            .all(|structure| structure.name().ends_with("/x-raw"));
+        if raw {
+            return true;
+        }
+        // The same elements decodebin considers when plugging a decoder.
+        gst::ElementFactory::factories_with_type(
+            gst::ElementFactoryType::DECODER | gst::ElementFactoryType::DEMUXER,
+            gst::Rank::MARGINAL,
+        )
+        .iter()
+        .any(|factory| factory.can_sink_all_caps(&caps))
+    }
+}
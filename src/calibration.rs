@@ -0,0 +1,282 @@
+extern crate gstreamer as gst;
+extern crate gstreamer_app as gst_app;
+extern crate gstreamer_audio as gst_audio;
+extern crate gstreamer_video as gst_video;
+
+use std::{f32::consts::TAU, time::Duration};
+
+use bevy::prelude::*;
+use gst::prelude::*;
+
+use crate::{
+    plugin::{insert_video_component, VideoPlayer, VideoPluginConfig},
+    video::VideoSource,
+};
+
+/// A flash and a beep start together at every multiple of this period.
+pub const PATTERN_PERIOD: Duration = Duration::from_secs(1);
+pub const PATTERN_PULSE: Duration = Duration::from_millis(100);
+
+const VIDEO_WIDTH: u32 = 320;
+const VIDEO_HEIGHT: u32 = 180;
+const VIDEO_FPS: u64 = 60;
+const AUDIO_RATE: u64 = 48_000;
+const AUDIO_CHUNK: u64 = 480;
+const BEEP_HZ: f32 = 1_000.0;
+
+fn in_pulse(ns: u64) -> bool {
+    let period = PATTERN_PERIOD.as_nanos() as u64;
+    let pulse = PATTERN_PULSE.as_nanos() as u64;
+    ns % period < pulse
+}
+
+/// Feeds the `calibration_video` and `calibration_audio` appsrcs of a
+/// `VideoSource::Calibration` pipeline.
+pub fn attach_calibration_sources(pipeline: &gst::Pipeline) {
+    if let Some(video) = pipeline
+        .by_name("calibration_video")
+        .and_then(|src| src.downcast::<gst_app::AppSrc>().ok())
+    {
+        let info =
+            gst_video::VideoInfo::builder(gst_video::VideoFormat::Rgba, VIDEO_WIDTH, VIDEO_HEIGHT)
+                .fps(gst::Fraction::new(VIDEO_FPS as i32, 1))
+                .build()
+                .expect("Failed to build calibration video info");
+        video.set_caps(Some(&info.to_caps().expect("calibration video caps")));
+        video.set_format(gst::Format::Time);
+        let frame_size = info.size();
+        let mut frame = 0u64;
+        video.set_callbacks(
+            gst_app::AppSrcCallbacks::builder()
+                .need_data(move |appsrc, _| {
+                    let pts = frame * 1_000_000_000 / VIDEO_FPS;
+                    let level = if in_pulse(pts) { 255 } else { 0 };
+                    let mut buffer =
+                        gst::Buffer::with_size(frame_size).expect("calibration buffer");
+                    {
+                        let buffer = buffer.get_mut().expect("calibration buffer");
+                        buffer.set_pts(gst::ClockTime::from_nseconds(pts));
+                        buffer
+                            .set_duration(gst::ClockTime::from_nseconds(1_000_000_000 / VIDEO_FPS));
+                        if let Ok(mut map) = buffer.map_writable() {
+                            for pixel in map.chunks_exact_mut(4) {
+                                pixel.copy_from_slice(&[level, level, level, 255]);
+                            }
+                        }
+                    }
+                    frame += 1;
+                    let _ = appsrc.push_buffer(buffer);
+                })
+                .build(),
+        );
+    }
+    if let Some(audio) = pipeline
+        .by_name("calibration_audio")
+        .and_then(|src| src.downcast::<gst_app::AppSrc>().ok())
+    {
+        let info =
+            gst_audio::AudioInfo::builder(gst_audio::AudioFormat::F32le, AUDIO_RATE as u32, 1)
+                .build()
+                .expect("Failed to build calibration audio info");
+        audio.set_caps(Some(&info.to_caps().expect("calibration audio caps")));
+        audio.set_format(gst::Format::Time);
+        let mut sample = 0u64;
+        audio.set_callbacks(
+            gst_app::AppSrcCallbacks::builder()
+                .need_data(move |appsrc, _| {
+                    let pts = sample * 1_000_000_000 / AUDIO_RATE;
+                    let data: Vec<u8> = (sample..sample + AUDIO_CHUNK)
+                        .flat_map(|index| {
+                            let ns = index * 1_000_000_000 / AUDIO_RATE;
+                            let value = if in_pulse(ns) {
+                                0.5 * (TAU * BEEP_HZ * index as f32 / AUDIO_RATE as f32).sin()
+                            } else {
+                                0.0
+                            };
+                            value.to_le_bytes()
+                        })
+                        .collect();
+                    let mut buffer = gst::Buffer::from_mut_slice(data);
+                    {
+                        let buffer = buffer.get_mut().expect("calibration buffer");
+                        buffer.set_pts(gst::ClockTime::from_nseconds(pts));
+                        buffer.set_duration(gst::ClockTime::from_nseconds(
+                            AUDIO_CHUNK * 1_000_000_000 / AUDIO_RATE,
+                        ));
+                    }
+                    sample += AUDIO_CHUNK;
+                    let _ = appsrc.push_buffer(buffer);
+                })
+                .build(),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CalibrationPhase {
+    /// Picture hidden; the user taps on every beep.
+    Audio,
+    /// Sound muted; the user taps on every flash.
+    Video,
+    Done,
+}
+
+/// State of a running calibration started with [`spawn_av_calibration`].
+/// Reaction time is the same for both phases, so the difference between the
+/// median tap delays is the AV offset of the display.
+#[derive(Resource, Debug)]
+pub struct AvCalibration {
+    pub player: Entity,
+    pub prompt: Entity,
+    pub phase: CalibrationPhase,
+    pub taps_per_phase: usize,
+    audio_taps: Vec<f64>,
+    video_taps: Vec<f64>,
+    /// Written to `VideoPluginConfig::av_offset_ms` when the flow finishes.
+    pub recommended_offset_ms: Option<i64>,
+}
+
+impl AvCalibration {
+    pub fn prompt_text(&self) -> String {
+        match self.phase {
+            CalibrationPhase::Audio => format!(
+                "Press space on every beep ({}/{})",
+                self.audio_taps.len(),
+                self.taps_per_phase
+            ),
+            CalibrationPhase::Video => format!(
+                "Press space on every flash ({}/{})",
+                self.video_taps.len(),
+                self.taps_per_phase
+            ),
+            CalibrationPhase::Done => format!(
+                "AV offset set to {} ms",
+                self.recommended_offset_ms.unwrap_or_default()
+            ),
+        }
+    }
+}
+
+fn median(values: &mut [f64]) -> f64 {
+    values.sort_by(|a, b| a.total_cmp(b));
+    values[values.len() / 2]
+}
+
+/// Delay of a tap after the closest preceding pulse, in milliseconds. Taps
+/// well before a pulse count as anticipation (negative).
+fn tap_delay_ms(position: Duration) -> f64 {
+    let period = PATTERN_PERIOD.as_secs_f64() * 1000.0;
+    let phase = (position.as_secs_f64() * 1000.0) % period;
+    if phase > period * 0.75 {
+        phase - period
+    } else {
+        phase
+    }
+}
+
+/// Spawns a calibration player with an instruction text and starts the flow.
+/// Taps are read from the space bar and the left mouse button.
+pub fn spawn_av_calibration(
+    commands: &mut Commands,
+    images: ResMut<Assets<Image>>,
+    size: Vec2,
+) -> Entity {
+    let mut video_player = VideoPlayer {
+        source: VideoSource::Calibration,
+        width: size.x,
+        height: size.y,
+        ..Default::default()
+    };
+    video_player.play();
+    let player = commands
+        .spawn(insert_video_component(images, size))
+        .insert(video_player)
+        .id();
+    // Not a child of the player: the player node is hidden during the
+    // audio phase.
+    let prompt = commands
+        .spawn(TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 18.0,
+                color: Color::ORANGE,
+                ..Default::default()
+            },
+        ))
+        .id();
+    commands.insert_resource(AvCalibration {
+        player,
+        prompt,
+        phase: CalibrationPhase::Audio,
+        taps_per_phase: 8,
+        audio_taps: Vec::new(),
+        video_taps: Vec::new(),
+        recommended_offset_ms: None,
+    });
+    player
+}
+
+pub fn run_av_calibration(
+    mut calibration: ResMut<AvCalibration>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut players: Query<(&mut VideoPlayer, &mut Visibility)>,
+    mut texts: Query<&mut Text>,
+    mut config: ResMut<VideoPluginConfig>,
+) {
+    let calibration = &mut *calibration;
+    let Ok((mut video_player, mut visibility)) = players.get_mut(calibration.player) else {
+        return;
+    };
+    let tapped = keys.just_pressed(KeyCode::Space) || mouse.just_pressed(MouseButton::Left);
+    let position = video_player
+        .pipeline
+        .as_ref()
+        .and_then(|pipeline| pipeline.position());
+
+    match calibration.phase {
+        CalibrationPhase::Audio => {
+            *visibility = Visibility::Hidden;
+            if let Some(pipeline) = video_player.pipeline.as_ref() {
+                pipeline.set_volume(1.0);
+            }
+            if let (true, Some(position)) = (tapped, position) {
+                calibration.audio_taps.push(tap_delay_ms(position));
+                if calibration.audio_taps.len() >= calibration.taps_per_phase {
+                    calibration.phase = CalibrationPhase::Video;
+                }
+            }
+        }
+        CalibrationPhase::Video => {
+            *visibility = Visibility::Inherited;
+            if let Some(pipeline) = video_player.pipeline.as_ref() {
+                pipeline.set_volume(0.0);
+            }
+            if let (true, Some(position)) = (tapped, position) {
+                calibration.video_taps.push(tap_delay_ms(position));
+                if calibration.video_taps.len() >= calibration.taps_per_phase {
+                    // Positive when the picture is perceived after the sound,
+                    // so video has to be shown earlier by that amount.
+                    let video_late =
+                        median(&mut calibration.video_taps) - median(&mut calibration.audio_taps);
+                    let offset = config.av_offset_ms - video_late.round() as i64;
+                    config.av_offset_ms = offset;
+                    calibration.recommended_offset_ms = Some(offset);
+                    calibration.phase = CalibrationPhase::Done;
+                    if let Some(pipeline) = video_player.pipeline.as_ref() {
+                        pipeline.set_volume(1.0);
+                    }
+                    video_player.stop();
+                }
+            }
+        }
+        CalibrationPhase::Done => {}
+    }
+
+    if let Ok(mut text) = texts.get_mut(calibration.prompt) {
+        let prompt = calibration.prompt_text();
+        if text.sections[0].value != prompt {
+            text.sections[0].value = prompt;
+        }
+    }
+}
@@ -0,0 +1,99 @@
+extern crate gstreamer as gst;
+extern crate gstreamer_video as gst_video;
+
+use bevy::reflect::Reflect;
+use gst::prelude::*;
+
+/// YUV to RGB matrix used for conversion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+pub enum ColorMatrix {
+    /// Use the stream's colorimetry, guessing from the resolution when the
+    /// stream doesn't say (BT.601 below 720 lines, BT.709 otherwise).
+    #[default]
+    Auto,
+    Bt601,
+    Bt709,
+    Bt2020,
+}
+
+/// Quantization range of the YUV samples.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+pub enum ColorRange {
+    /// Use the stream's colorimetry, assuming limited range when unknown.
+    #[default]
+    Auto,
+    /// 16-235 (broadcast/most files).
+    Limited,
+    /// 0-255 (JPEG, many screen recordings).
+    Full,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+pub struct ColorSpace {
+    pub matrix: ColorMatrix,
+    pub range: ColorRange,
+}
+
+impl ColorSpace {
+    /// Colorimetry the converter should assume for a stream with `info`.
+    pub fn resolve(&self, info: &gst_video::VideoInfo) -> gst_video::VideoColorimetry {
+        let colorimetry = info.colorimetry();
+        let matrix = match self.matrix {
+            ColorMatrix::Bt601 => gst_video::VideoColorMatrix::Bt601,
+            ColorMatrix::Bt709 => gst_video::VideoColorMatrix::Bt709,
+            ColorMatrix::Bt2020 => gst_video::VideoColorMatrix::Bt2020,
+            ColorMatrix::Auto => match colorimetry.matrix() {
+                gst_video::VideoColorMatrix::Unknown if info.height() < 720 => {
+                    gst_video::VideoColorMatrix::Bt601
+                }
+                gst_video::VideoColorMatrix::Unknown => gst_video::VideoColorMatrix::Bt709,
+                matrix => matrix,
+            },
+        };
+        let range = match self.range {
+            ColorRange::Limited => gst_video::VideoColorRange::Range16_235,
+            ColorRange::Full => gst_video::VideoColorRange::Range0_255,
+            ColorRange::Auto => match colorimetry.range() {
+                gst_video::VideoColorRange::Unknown => gst_video::VideoColorRange::Range16_235,
+                range => range,
+            },
+        };
+        gst_video::VideoColorimetry::new(
+            range,
+            matrix,
+            colorimetry.transfer(),
+            colorimetry.primaries(),
+        )
+    }
+}
+
+/// Rewrites the colorimetry of YUV caps entering `element` (normally the
+/// videoconvert) so the matrix and range it converts with are never guessed
+/// wrong or left unknown.
+pub fn apply_color_space(element: &gst::Element, color_space: ColorSpace) {
+    let Some(pad) = element.static_pad("sink") else {
+        return;
+    };
+    pad.add_probe(gst::PadProbeType::EVENT_DOWNSTREAM, move |_, probe_info| {
+        let Some(gst::PadProbeData::Event(event)) = probe_info.data.as_ref() else {
+            return gst::PadProbeReturn::Ok;
+        };
+        let gst::EventView::Caps(caps_event) = event.view() else {
+            return gst::PadProbeReturn::Ok;
+        };
+        let Ok(info) = gst_video::VideoInfo::from_caps(caps_event.caps()) else {
+            return gst::PadProbeReturn::Ok;
+        };
+        if !info.format_info().is_yuv() {
+            return gst::PadProbeReturn::Ok;
+        }
+        let colorimetry = color_space.resolve(&info);
+        if colorimetry == info.colorimetry() {
+            return gst::PadProbeReturn::Ok;
+        }
+        let mut caps = caps_event.caps_owned();
+        caps.make_mut().set("colorimetry", colorimetry.to_string());
+        probe_info.data = Some(gst::PadProbeData::Event(gst::event::Caps::new(&caps)));
+        gst::PadProbeReturn::Ok
+    });
+}
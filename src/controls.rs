@@ -0,0 +1,497 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use bevy::{prelude::*, ui::RelativeCursorPosition};
+
+use crate::{
+    plugin::{frame_to_image, VideoPlayer, VideoState},
+    thumbnail::{ThumbnailError, VideoThumbnailer},
+    video::{VideoInfo, VideoSource},
+};
+
+/// Play/pause button, seek bar, timestamp and volume slider for the
+/// `VideoPlayer` on `player`. The children are spawned by the plugin the
+/// frame after the bundle is added.
+#[derive(Bundle)]
+pub struct VideoControlsBundle {
+    pub node: NodeBundle,
+    pub controls: VideoControls,
+}
+
+impl VideoControlsBundle {
+    pub fn new(player: Entity) -> Self {
+        VideoControlsBundle {
+            node: NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Px(32.0),
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(8.0),
+                    padding: UiRect::horizontal(Val::Px(8.0)),
+                    ..Default::default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.6).into(),
+                ..Default::default()
+            },
+            controls: VideoControls { player },
+        }
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy)]
+pub struct VideoControls {
+    pub player: Entity,
+}
+
+/// Add next to a `VideoControlsBundle` to show a preview frame above the
+/// seek bar while it is hovered. The frames come from a strip decoded once
+/// on a background thread, so this only works for `VideoSource::Uri`.
+/// Removing the component or changing the source stops the decode.
+#[derive(Component)]
+pub struct ScrubPreviews {
+    /// Number of frames spread over the stream.
+    pub count: usize,
+    /// Logical size of the preview; frames are decoded at this size.
+    pub size: UVec2,
+    pub frames: Vec<(Duration, Handle<Image>)>,
+    strip: Option<ScrubStrip>,
+}
+
+type StripResult = Result<Vec<(Duration, VideoInfo)>, ThumbnailError>;
+
+/// Strip decoding on its own thread for `uri`. Dropping it stops the
+/// decode before the next frame.
+struct ScrubStrip {
+    uri: String,
+    result: Arc<Mutex<Option<StripResult>>>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl ScrubStrip {
+    fn spawn(uri: &str, count: usize, thumbnailer: VideoThumbnailer) -> Self {
+        let result = Arc::new(Mutex::new(None));
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let (thread_uri, thread_result, thread_cancelled) =
+            (uri.to_string(), Arc::clone(&result), Arc::clone(&cancelled));
+        let spawned = thread::Builder::new()
+            .name("video-scrub-strip".to_string())
+            .spawn(move || {
+                let frames =
+                    thumbnailer.grab_strip_cancellable(&thread_uri, count, &thread_cancelled);
+                if let Ok(mut result) = thread_result.lock() {
+                    *result = Some(frames);
+                }
+            });
+        if let Err(err) = spawned {
+            if let Ok(mut result) = result.lock() {
+                *result = Some(Err(ThumbnailError::Pipeline(err.to_string())));
+            }
+        }
+        ScrubStrip {
+            uri: uri.to_string(),
+            result,
+            cancelled,
+        }
+    }
+
+    fn take(&self) -> Option<StripResult> {
+        self.result.lock().ok().and_then(|mut result| result.take())
+    }
+}
+
+impl Drop for ScrubStrip {
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+}
+
+impl Default for ScrubPreviews {
+    fn default() -> Self {
+        ScrubPreviews {
+            count: 40,
+            size: UVec2::new(160, 90),
+            frames: Vec::new(),
+            strip: None,
+        }
+    }
+}
+
+impl ScrubPreviews {
+    /// Preview closest to `time`.
+    pub fn frame_at(&self, time: Duration) -> Option<&Handle<Image>> {
+        self.frames
+            .iter()
+            .min_by_key(|(timestamp, _)| timestamp.abs_diff(time))
+            .map(|(_, handle)| handle)
+    }
+}
+
+/// Parts of a controls widget, each pointing back at the player entity.
+#[derive(Component, Debug, Clone, Copy)]
+pub enum VideoControl {
+    PlayPause(Entity),
+    PlayPauseLabel(Entity),
+    SeekBar(Entity),
+    SeekFill(Entity),
+    Timestamp(Entity),
+    VolumeBar(Entity),
+    VolumeFill(Entity),
+    Preview(Entity),
+}
+
+const TRACK_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.25);
+const FILL_COLOR: Color = Color::WHITE;
+
+fn text_style() -> TextStyle {
+    TextStyle {
+        font_size: 14.0,
+        color: Color::WHITE,
+        ..Default::default()
+    }
+}
+
+fn spawn_track(
+    parent: &mut ChildBuilder,
+    width: Val,
+    bar: VideoControl,
+    fill: VideoControl,
+    preview: Option<UVec2>,
+) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    width,
+                    height: Val::Px(6.0),
+                    // Only the seek bar is `Auto`, it takes the space left.
+                    flex_grow: if width == Val::Auto { 1.0 } else { 0.0 },
+                    ..Default::default()
+                },
+                background_color: TRACK_COLOR.into(),
+                ..Default::default()
+            },
+            RelativeCursorPosition::default(),
+            bar,
+        ))
+        .with_children(|track| {
+            track.spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(0.0),
+                        height: Val::Percent(100.0),
+                        ..Default::default()
+                    },
+                    background_color: FILL_COLOR.into(),
+                    ..Default::default()
+                },
+                fill,
+            ));
+            let (VideoControl::SeekBar(player), Some(size)) = (bar, preview) else {
+                return;
+            };
+            track.spawn((
+                ImageBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        bottom: Val::Px(12.0),
+                        width: Val::Px(size.x as f32),
+                        height: Val::Px(size.y as f32),
+                        margin: UiRect::left(Val::Px(-(size.x as f32) / 2.0)),
+                        ..Default::default()
+                    },
+                    visibility: Visibility::Hidden,
+                    ..Default::default()
+                },
+                VideoControl::Preview(player),
+            ));
+        });
+}
+
+/// Spawns the children of newly added `VideoControlsBundle`s.
+pub fn build_video_controls(
+    mut commands: Commands,
+    query: Query<(Entity, &VideoControls, Option<&ScrubPreviews>), Added<VideoControls>>,
+) {
+    for (entity, controls, previews) in query.iter() {
+        let preview = previews.map(|previews| previews.size);
+        let player = controls.player;
+        commands.entity(entity).with_children(|parent| {
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            width: Val::Px(56.0),
+                            justify_content: JustifyContent::Center,
+                            ..Default::default()
+                        },
+                        background_color: Color::NONE.into(),
+                        ..Default::default()
+                    },
+                    VideoControl::PlayPause(player),
+                ))
+                .with_children(|button| {
+                    button.spawn((
+                        TextBundle::from_section("Play", text_style()),
+                        VideoControl::PlayPauseLabel(player),
+                    ));
+                });
+            spawn_track(
+                parent,
+                Val::Auto,
+                VideoControl::SeekBar(player),
+                VideoControl::SeekFill(player),
+                preview,
+            );
+            parent.spawn((
+                TextBundle::from_section("0:00 / 0:00", text_style()),
+                VideoControl::Timestamp(player),
+            ));
+            spawn_track(
+                parent,
+                Val::Px(64.0),
+                VideoControl::VolumeBar(player),
+                VideoControl::VolumeFill(player),
+                None,
+            );
+        });
+    }
+}
+
+/// Turns clicks on the controls into player commands.
+pub fn handle_video_controls(
+    controls: Query<
+        (&Interaction, &VideoControl, Option<&RelativeCursorPosition>),
+        Changed<Interaction>,
+    >,
+    mut players: Query<&mut VideoPlayer>,
+) {
+    for (interaction, control, cursor) in controls.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let position = cursor
+            .and_then(|cursor| cursor.normalized)
+            .map(|position| position.x.clamp(0.0, 1.0));
+        match *control {
+            VideoControl::PlayPause(player) => {
+                if let Ok(mut video_player) = players.get_mut(player) {
+                    if video_player.state() == VideoState::Playing {
+                        video_player.pause();
+                    } else {
+                        video_player.play();
+                    }
+                }
+            }
+            VideoControl::SeekBar(player) => {
+                let (Ok(mut video_player), Some(position)) = (players.get_mut(player), position)
+                else {
+                    continue;
+                };
+                let duration = video_player.share().and_then(|shared| shared.duration());
+                if let Some(duration) = duration {
+                    video_player.seek(duration.mul_f32(position));
+                }
+            }
+            VideoControl::VolumeBar(player) => {
+                let (Ok(video_player), Some(position)) = (players.get(player), position) else {
+                    continue;
+                };
+                if let Some(pipeline) = video_player.pipeline.as_ref() {
+                    pipeline.set_volume(position);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn format_timestamp(time: Duration) -> String {
+    let seconds = time.as_secs();
+    if seconds >= 3600 {
+        format!(
+            "{}:{:02}:{:02}",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        )
+    } else {
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    }
+}
+
+fn set_fill(style: &mut Mut<Style>, fraction: f32) {
+    let width = Val::Percent(fraction.clamp(0.0, 1.0) * 100.0);
+    if style.width != width {
+        style.width = width;
+    }
+}
+
+/// Refreshes labels, the seek position and the volume level.
+pub fn update_video_controls(
+    players: Query<&VideoPlayer>,
+    mut parts: Query<(&VideoControl, Option<&mut Text>, Option<&mut Style>)>,
+) {
+    for (control, text, style) in parts.iter_mut() {
+        match *control {
+            VideoControl::PlayPauseLabel(player) => {
+                let (Ok(video_player), Some(mut text)) = (players.get(player), text) else {
+                    continue;
+                };
+                let label = if video_player.state() == VideoState::Playing {
+                    "Pause"
+                } else {
+                    "Play"
+                };
+                if text.sections[0].value != label {
+                    text.sections[0].value = label.to_string();
+                }
+            }
+            VideoControl::Timestamp(player) => {
+                let (Ok(video_player), Some(mut text)) = (players.get(player), text) else {
+                    continue;
+                };
+                let Some(shared) = video_player.share() else {
+                    continue;
+                };
+                let label = format!(
+                    "{} / {}",
+                    format_timestamp(shared.position().unwrap_or_default()),
+                    format_timestamp(shared.duration().unwrap_or_default())
+                );
+                if text.sections[0].value != label {
+                    text.sections[0].value = label;
+                }
+            }
+            VideoControl::SeekFill(player) => {
+                let (Ok(video_player), Some(mut style)) = (players.get(player), style) else {
+                    continue;
+                };
+                let Some(shared) = video_player.share() else {
+                    continue;
+                };
+                let progress = match (shared.position(), shared.duration()) {
+                    (Some(position), Some(duration)) if !duration.is_zero() => {
+                        position.as_secs_f32() / duration.as_secs_f32()
+                    }
+                    _ => 0.0,
+                };
+                set_fill(&mut style, progress);
+            }
+            VideoControl::VolumeFill(player) => {
+                let (Ok(video_player), Some(mut style)) = (players.get(player), style) else {
+                    continue;
+                };
+                let volume = video_player
+                    .pipeline
+                    .as_ref()
+                    .map(|pipeline| pipeline.volume())
+                    .unwrap_or(1.0);
+                set_fill(&mut style, volume);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Decodes the preview strip of each `ScrubPreviews` on a background thread
+/// and turns it into textures once ready.
+pub fn load_scrub_previews(
+    mut query: Query<(&VideoControls, &mut ScrubPreviews)>,
+    players: Query<&VideoPlayer>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    for (controls, mut previews) in query.iter_mut() {
+        let uri = match players.get(controls.player).map(|player| &player.source) {
+            Ok(VideoSource::Uri(uri)) => Some(uri),
+            _ => None,
+        };
+        if previews
+            .strip
+            .as_ref()
+            .is_some_and(|strip| Some(&strip.uri) != uri)
+        {
+            previews.strip = None;
+            previews.frames.clear();
+        }
+        let Some(strip) = previews.strip.as_ref() else {
+            let Some(uri) = uri else {
+                continue;
+            };
+            let thumbnailer = VideoThumbnailer {
+                size: Some((previews.size.x, previews.size.y)),
+                ..Default::default()
+            };
+            previews.strip = Some(ScrubStrip::spawn(uri, previews.count, thumbnailer));
+            continue;
+        };
+        let Some(result) = strip.take() else {
+            continue;
+        };
+        match result {
+            Ok(frames) => {
+                previews.frames = frames
+                    .into_iter()
+                    .filter_map(|(timestamp, info)| {
+                        let image = frame_to_image(info, Default::default())?;
+                        Some((timestamp, images.add(image)))
+                    })
+                    .collect();
+            }
+            Err(err) => warn!("Failed to decode scrub previews: {err}"),
+        }
+    }
+}
+
+/// Shows the preview for the hovered seek bar position.
+pub fn update_scrub_previews(
+    previews: Query<(&VideoControls, &ScrubPreviews)>,
+    players: Query<&VideoPlayer>,
+    bars: Query<(&VideoControl, &RelativeCursorPosition)>,
+    mut parts: Query<(&VideoControl, &mut Style, &mut UiImage, &mut Visibility)>,
+) {
+    for (bar, cursor) in bars.iter() {
+        let VideoControl::SeekBar(player) = *bar else {
+            continue;
+        };
+        let Some((_, previews)) = previews
+            .iter()
+            .find(|(controls, _)| controls.player == player)
+        else {
+            continue;
+        };
+        let duration = players
+            .get(player)
+            .ok()
+            .and_then(|video_player| video_player.share())
+            .and_then(|shared| shared.duration());
+        let hovered = cursor
+            .normalized
+            .filter(|_| cursor.mouse_over())
+            .map(|position| position.x.clamp(0.0, 1.0));
+        let frame = hovered
+            .zip(duration)
+            .and_then(|(position, duration)| previews.frame_at(duration.mul_f32(position)));
+        for (control, mut style, mut image, mut visibility) in parts.iter_mut() {
+            if !matches!(*control, VideoControl::Preview(p) if p == player) {
+                continue;
+            }
+            let (Some(position), Some(frame)) = (hovered, frame) else {
+                if *visibility != Visibility::Hidden {
+                    *visibility = Visibility::Hidden;
+                }
+                continue;
+            };
+            *visibility = Visibility::Inherited;
+            style.left = Val::Percent(position * 100.0);
+            if image.texture != *frame {
+                image.texture = frame.clone();
+            }
+        }
+    }
+}
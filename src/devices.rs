@@ -0,0 +1,77 @@
+extern crate gstreamer as gst;
+
+use gst::prelude::*;
+
+use crate::init::ensure_gstreamer;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CaptureDeviceKind {
+    Camera,
+    Microphone,
+    /// A monitor or window that can be captured.
+    Screen,
+}
+
+/// Capture device found by `capture_devices`, for a device picker.
+#[derive(Debug, Clone)]
+pub struct CaptureDevice {
+    pub name: String,
+    pub kind: CaptureDeviceKind,
+    /// GStreamer device class, e.g. `"Video/Source"`.
+    pub class: String,
+    /// Formats the device can produce, one caps structure each, e.g.
+    /// `"video/x-raw, format=(string)YUY2, width=(int)640, ..."`.
+    pub caps: Vec<String>,
+    /// Creates the source element through `create_element`.
+    pub device: gst::Device,
+}
+
+impl CaptureDevice {
+    fn from_device(device: gst::Device) -> Option<Self> {
+        let class = device.device_class().to_string();
+        let kind = if class.contains("Monitor") {
+            CaptureDeviceKind::Screen
+        } else if class.contains("Video/Source") {
+            CaptureDeviceKind::Camera
+        } else if class.contains("Audio/Source") {
+            CaptureDeviceKind::Microphone
+        } else {
+            return None;
+        };
+        let caps = device
+            .caps()
+            .map(|caps| caps.iter().map(|structure| structure.to_string()).collect())
+            .unwrap_or_default();
+        Some(CaptureDevice {
+            name: device.display_name().to_string(),
+            kind,
+            class,
+            caps,
+            device,
+        })
+    }
+}
+
+/// Cameras, microphones and screens currently available, as reported by
+/// the platform's device providers. Probing can take a moment, so call it
+/// when the picker opens rather than every frame. Empty if GStreamer fails
+/// to initialize or no provider could be started.
+pub fn capture_devices() -> Vec<CaptureDevice> {
+    if ensure_gstreamer().is_err() {
+        return Vec::new();
+    }
+    let monitor = gst::DeviceMonitor::new();
+    for class in ["Video/Source", "Audio/Source", "Source/Monitor"] {
+        monitor.add_filter(Some(class), None);
+    }
+    if monitor.start().is_err() {
+        return Vec::new();
+    }
+    let devices = monitor
+        .devices()
+        .into_iter()
+        .filter_map(CaptureDevice::from_device)
+        .collect();
+    monitor.stop();
+    devices
+}
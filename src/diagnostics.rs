@@ -0,0 +1,114 @@
+use std::{collections::HashMap, time::Duration};
+
+use bevy::{
+    diagnostic::{Diagnostic, DiagnosticMeasurement, DiagnosticPath, DiagnosticsStore},
+    prelude::*,
+    utils::Instant,
+};
+
+use crate::plugin::VideoPlayer;
+
+/// Per-player measurement, with its path segment and suffix.
+const MEASUREMENTS: [(&str, &str); 7] = [
+    ("decoded_fps", "fps"),
+    ("displayed_fps", "fps"),
+    ("dropped_frames", ""),
+    ("queue_depth", " frames"),
+    ("queue_memory", "MB"),
+    ("memory_capped_frames", ""),
+    ("upload_time", "ms"),
+];
+
+/// Adds per-player diagnostics under `video/<entity>/`: decoded and
+/// displayed frame rates, dropped frames, frame queue depth and memory,
+/// frames hit by the queue's byte limit and frame upload time. They show
+/// up in `LogDiagnosticsPlugin` and other overlays reading
+/// `DiagnosticsStore`. Diagnostics of despawned players are disabled, since
+/// the store cannot remove them.
+pub struct VideoDiagnosticsPlugin;
+
+impl Plugin for VideoDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DiagnosticsStore>()
+            .add_systems(PostUpdate, update_video_diagnostics);
+    }
+}
+
+/// Path of the player diagnostic `name`, e.g. `video/4v1/decoded_fps`.
+pub fn video_diagnostic_path(entity: Entity, name: &str) -> DiagnosticPath {
+    DiagnosticPath::new(format!(
+        "video/{}v{}/{name}",
+        entity.index(),
+        entity.generation()
+    ))
+}
+
+/// What the previous measurement saw, to turn totals into rates.
+struct Previous {
+    video_samples: u64,
+    presentation: Option<(Duration, Duration)>,
+}
+
+fn update_video_diagnostics(
+    query: Query<(Entity, &VideoPlayer)>,
+    mut store: ResMut<DiagnosticsStore>,
+    time: Res<Time>,
+    mut previous: Local<HashMap<Entity, Previous>>,
+) {
+    let now = Instant::now();
+    let delta = time.delta_seconds_f64();
+    for (entity, video_player) in query.iter() {
+        let Some(pipeline) = video_player.pipeline.as_ref() else {
+            continue;
+        };
+        let stats = pipeline
+            .stats
+            .lock()
+            .map(|stats| *stats)
+            .unwrap_or_default();
+        let (queued_frames, queued_bytes) = pipeline.queued_frames();
+        let presentation = video_player.presentation_anchor;
+        let last = previous.entry(entity).or_insert_with(|| {
+            for (name, suffix) in MEASUREMENTS {
+                store.add(Diagnostic::new(video_diagnostic_path(entity, name)).with_suffix(suffix));
+            }
+            Previous {
+                video_samples: stats.video_samples,
+                presentation,
+            }
+        });
+        let presented = presentation.is_some() && presentation != last.presentation;
+        let per_second = |count: f64| if delta > 0.0 { count / delta } else { 0.0 };
+        let values = [
+            per_second(stats.video_samples.saturating_sub(last.video_samples) as f64),
+            per_second(if presented { 1.0 } else { 0.0 }),
+            stats.dropped_frames as f64,
+            queued_frames as f64,
+            queued_bytes as f64 / (1024.0 * 1024.0),
+            stats.memory_capped_frames as f64,
+            video_player.upload_time.as_secs_f64() * 1000.0,
+        ];
+        last.video_samples = stats.video_samples;
+        last.presentation = presentation;
+        for ((name, _), value) in MEASUREMENTS.into_iter().zip(values) {
+            // Upload time only changes when a frame is presented.
+            if name == "upload_time" && !presented {
+                continue;
+            }
+            if let Some(diagnostic) = store.get_mut(&video_diagnostic_path(entity, name)) {
+                diagnostic.add_measurement(DiagnosticMeasurement { time: now, value });
+            }
+        }
+    }
+    previous.retain(|entity, _| {
+        let alive = query.contains(*entity);
+        if !alive {
+            for (name, _) in MEASUREMENTS {
+                if let Some(diagnostic) = store.get_mut(&video_diagnostic_path(*entity, name)) {
+                    diagnostic.is_enabled = false;
+                }
+            }
+        }
+        alive
+    });
+}
@@ -0,0 +1,180 @@
+use std::ops::Range;
+
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssets,
+        render_resource::{Extent3d, ImageCopyTexture, ImageDataLayout, Origin3d, TextureAspect},
+        renderer::RenderQueue,
+        Render, RenderApp, RenderSet,
+    },
+};
+
+/// Uploads only the rows that changed since the previous frame, for
+/// slides and screen shares where most of the picture is static.
+///
+/// The first frame, and any frame whose size or format changes, is still
+/// uploaded as a whole texture.
+#[derive(Debug, Clone, Default)]
+pub struct DirtyRegions {
+    /// Frame currently on screen; the texture asset itself is not updated
+    /// on the CPU side once partial uploads begin.
+    pub frame: Option<Image>,
+}
+
+impl DirtyRegions {
+    /// Records `next` and returns the bands of rows that differ from the
+    /// previous frame, or `None` if it must be uploaded in full.
+    pub fn update(&mut self, next: Image) -> Option<Vec<Range<u32>>> {
+        let bands = self.frame.as_ref().and_then(|previous| {
+            let same_layout = previous.texture_descriptor.size == next.texture_descriptor.size
+                && previous.texture_descriptor.format == next.texture_descriptor.format
+                && previous.data.len() == next.data.len();
+            same_layout.then(|| changed_rows(&previous.data, &next.data, next.height()))
+        });
+        self.frame = Some(next);
+        bands
+    }
+}
+
+/// Bands of consecutive rows that differ between two equally sized frames.
+pub fn changed_rows(previous: &[u8], next: &[u8], height: u32) -> Vec<Range<u32>> {
+    let mut bands: Vec<Range<u32>> = Vec::new();
+    if height == 0 {
+        return bands;
+    }
+    let row_bytes = next.len() / height as usize;
+    let rows = previous
+        .chunks_exact(row_bytes)
+        .zip(next.chunks_exact(row_bytes));
+    for (row, (before, after)) in rows.enumerate() {
+        if before == after {
+            continue;
+        }
+        let row = row as u32;
+        match bands.last_mut() {
+            Some(band) if band.end == row => band.end = row + 1,
+            _ => bands.push(row..row + 1),
+        }
+    }
+    bands
+}
+
+/// A band of rows to write into an existing texture.
+#[derive(Debug, Clone)]
+pub struct RegionUpload {
+    pub image: AssetId<Image>,
+    pub rows: Range<u32>,
+    pub row_bytes: u32,
+    pub data: Vec<u8>,
+}
+
+/// Partial uploads queued this frame, moved to the render world on extract.
+#[derive(Resource, Debug, Default)]
+pub struct DirtyRegionUploads(pub Vec<RegionUpload>);
+
+impl DirtyRegionUploads {
+    /// Queues the `bands` of `frame` for the texture behind `image`.
+    pub fn push(&mut self, image: AssetId<Image>, frame: &Image, bands: &[Range<u32>]) {
+        let height = frame.height().max(1);
+        let row_bytes = frame.data.len() as u32 / height;
+        for rows in bands {
+            let start = (rows.start * row_bytes) as usize;
+            let end = (rows.end * row_bytes) as usize;
+            self.0.push(RegionUpload {
+                image,
+                rows: rows.clone(),
+                row_bytes,
+                data: frame.data[start..end].to_vec(),
+            });
+        }
+    }
+}
+
+fn extract_dirty_regions(mut main_world: ResMut<bevy::render::MainWorld>, mut commands: Commands) {
+    if let Some(mut uploads) = main_world.get_resource_mut::<DirtyRegionUploads>() {
+        commands.insert_resource(DirtyRegionUploads(std::mem::take(&mut uploads.0)));
+    }
+}
+
+fn upload_dirty_regions(
+    uploads: Res<DirtyRegionUploads>,
+    gpu_images: Res<RenderAssets<Image>>,
+    render_queue: Res<RenderQueue>,
+) {
+    let _span = info_span!("video_dirty_region_upload", bands = uploads.0.len()).entered();
+    for upload in uploads.0.iter() {
+        let Some(gpu_image) = gpu_images.get(upload.image) else {
+            continue;
+        };
+        render_queue.write_texture(
+            ImageCopyTexture {
+                texture: &gpu_image.texture,
+                mip_level: 0,
+                origin: Origin3d {
+                    x: 0,
+                    y: upload.rows.start,
+                    z: 0,
+                },
+                aspect: TextureAspect::All,
+            },
+            &upload.data,
+            ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(upload.row_bytes),
+                rows_per_image: None,
+            },
+            Extent3d {
+                width: gpu_image.size.x as u32,
+                height: upload.rows.end - upload.rows.start,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+}
+
+/// Registers the render-world half of dirty-region uploads.
+pub(crate) fn build_dirty_regions(app: &mut App) {
+    app.init_resource::<DirtyRegionUploads>();
+    if let Ok(render_app) = app.get_sub_app_mut(RenderApp) {
+        render_app
+            .init_resource::<DirtyRegionUploads>()
+            .add_systems(ExtractSchedule, extract_dirty_regions)
+            .add_systems(
+                Render,
+                upload_dirty_regions.in_set(RenderSet::PrepareResources),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changed_rows_merges_adjacent_rows() {
+        let previous = [0u8; 12];
+        let mut next = previous;
+        // Rows of two bytes: change rows 1, 2 and 4.
+        next[2] = 1;
+        next[5] = 1;
+        next[8] = 1;
+        assert_eq!(changed_rows(&previous, &next, 6), [1..3, 4..5]);
+    }
+
+    #[test]
+    fn changed_rows_of_identical_frames_is_empty() {
+        let frame = [7u8; 12];
+        assert!(changed_rows(&frame, &frame, 3).is_empty());
+        assert!(changed_rows(&[], &[], 0).is_empty());
+    }
+
+    #[test]
+    fn changed_rows_covers_first_and_last_row() {
+        let previous = [0u8; 9];
+        let next = [1u8; 9];
+        let bands = changed_rows(&previous, &next, 3);
+        assert_eq!(bands.len(), 1);
+        assert_eq!(bands[0], 0..3);
+    }
+}
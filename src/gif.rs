@@ -0,0 +1,99 @@
+extern crate gstreamer as gst;
+extern crate gstreamer_app as gst_app;
+extern crate gstreamer_video as gst_video;
+
+use std::{fs::File, io::BufReader, time::Duration};
+
+use gst::prelude::*;
+use image::{codecs::gif::GifDecoder, AnimationDecoder, RgbaImage};
+
+/// Browsers show frames with a delay below this at the default speed, and
+/// GIFs are authored for browsers.
+const MIN_DELAY: Duration = Duration::from_millis(20);
+const DEFAULT_DELAY: Duration = Duration::from_millis(100);
+
+/// Composited frames of a GIF and how long each one shows.
+fn decode_gif(path: &str) -> Result<Vec<(RgbaImage, Duration)>, String> {
+    let file = File::open(path).map_err(|err| err.to_string())?;
+    let decoder = GifDecoder::new(BufReader::new(file)).map_err(|err| err.to_string())?;
+    decoder
+        .into_frames()
+        .map(|frame| {
+            let frame = frame.map_err(|err| err.to_string())?;
+            let (numer, denom) = frame.delay().numer_denom_ms();
+            let delay = Duration::from_micros(numer as u64 * 1000 / denom.max(1) as u64);
+            let delay = if delay < MIN_DELAY {
+                DEFAULT_DELAY
+            } else {
+                delay
+            };
+            Ok((frame.into_buffer(), delay))
+        })
+        .collect()
+}
+
+/// Feeds the `gif_source` appsrc of a `VideoSource::Gif` pipeline. GIFs
+/// are decoded with the `image` crate rather than through decodebin, which
+/// shows only the first frame or ignores the per-frame delays. The file is
+/// decoded on the streaming thread and loops forever.
+pub fn attach_gif_source(pipeline: &gst::Pipeline, path: &str) {
+    let Some(appsrc) = pipeline
+        .by_name("gif_source")
+        .and_then(|src| src.downcast::<gst_app::AppSrc>().ok())
+    else {
+        return;
+    };
+    appsrc.set_format(gst::Format::Time);
+    let path = path.to_string();
+    let mut frames: Option<Vec<(RgbaImage, Duration)>> = None;
+    let mut index = 0;
+    let mut pts = Duration::ZERO;
+    appsrc.set_callbacks(
+        gst_app::AppSrcCallbacks::builder()
+            .need_data(move |appsrc, _| {
+                if frames.is_none() {
+                    match decode_gif(&path) {
+                        Ok(decoded) if !decoded.is_empty() => {
+                            let (first, _) = &decoded[0];
+                            let info = gst_video::VideoInfo::builder(
+                                gst_video::VideoFormat::Rgba,
+                                first.width(),
+                                first.height(),
+                            )
+                            .fps(gst::Fraction::new(0, 1))
+                            .build()
+                            .expect("Failed to build GIF video info");
+                            appsrc.set_caps(Some(&info.to_caps().expect("GIF caps")));
+                            frames = Some(decoded);
+                        }
+                        Ok(_) => {
+                            let _ = appsrc.end_of_stream();
+                            return;
+                        }
+                        Err(err) => {
+                            gst::element_error!(
+                                appsrc,
+                                gst::StreamError::Decode,
+                                ("Failed to decode GIF {}: {}", path, err)
+                            );
+                            return;
+                        }
+                    }
+                }
+                let Some(frames) = frames.as_ref() else {
+                    return;
+                };
+                let (image, delay) = &frames[index];
+                let mut buffer = gst::Buffer::from_slice(image.as_raw().clone());
+                {
+                    let buffer = buffer.get_mut().expect("GIF buffer");
+                    buffer.set_pts(gst::ClockTime::from_nseconds(pts.as_nanos() as u64));
+                    buffer.set_duration(gst::ClockTime::from_nseconds(delay.as_nanos() as u64));
+                }
+                pts += *delay;
+                index = (index + 1) % frames.len();
+                let _ = appsrc.push_buffer(buffer);
+            })
+            .build(),
+    );
+}
@@ -0,0 +1,192 @@
+extern crate gstreamer_video as gst_video;
+
+use std::sync::OnceLock;
+
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+
+/// Transfer function of the decoded stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+pub enum VideoTransfer {
+    #[default]
+    Sdr,
+    /// SMPTE ST 2084 (HDR10, Dolby Vision base layer).
+    Pq,
+    /// ARIB STD-B67 hybrid log-gamma.
+    Hlg,
+}
+
+impl VideoTransfer {
+    pub fn from_video_info(info: &gst_video::VideoInfo) -> Self {
+        match info.colorimetry().transfer() {
+            gst_video::VideoTransferFunction::Smpte2084 => VideoTransfer::Pq,
+            gst_video::VideoTransferFunction::AribStdB67 => VideoTransfer::Hlg,
+            _ => VideoTransfer::Sdr,
+        }
+    }
+
+    pub fn is_hdr(self) -> bool {
+        self != VideoTransfer::Sdr
+    }
+}
+
+/// What to do with 16-bit frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+pub enum HdrMode {
+    /// Convert to an 8-bit sRGB texture on the CPU, tonemapping HDR content.
+    #[default]
+    Tonemap,
+    /// Upload the samples unchanged as `Rgba16Unorm`. The texture still holds
+    /// PQ/HLG encoded BT.2020 values; a custom material should read
+    /// `VideoPlayer::transfer` and tonemap. Needs a GPU with 16-bit norm
+    /// texture support.
+    Passthrough,
+}
+
+/// Reference white in nits that maps to 1.0 in SDR.
+const SDR_WHITE_NITS: f32 = 203.0;
+/// Brightest input level kept distinguishable by the tonemapper.
+const PEAK_NITS: f32 = 1000.0;
+
+const BT2020_TO_BT709: [[f32; 3]; 3] = [
+    [1.6605, -0.5876, -0.0728],
+    [-0.1246, 1.1329, -0.0083],
+    [-0.0182, -0.1006, 1.1187],
+];
+
+fn pq_to_nits(value: f32) -> f32 {
+    const M1: f32 = 0.159_301_76;
+    const M2: f32 = 78.843_75;
+    const C1: f32 = 0.835_937_5;
+    const C2: f32 = 18.851_563;
+    const C3: f32 = 18.6875;
+    let np = value.powf(1.0 / M2);
+    ((np - C1).max(0.0) / (C2 - C3 * np)).powf(1.0 / M1) * 10_000.0
+}
+
+fn hlg_to_nits(value: f32) -> f32 {
+    const A: f32 = 0.178_832_77;
+    const B: f32 = 0.284_668_92;
+    const C: f32 = 0.559_910_7;
+    let scene = if value <= 0.5 {
+        value * value / 3.0
+    } else {
+        (((value - C) / A).exp() + B) / 12.0
+    };
+    scene * PEAK_NITS
+}
+
+/// Linear light (1.0 = SDR white) for every 16-bit code value.
+fn linear_lut(transfer: VideoTransfer) -> &'static [f32] {
+    static PQ: OnceLock<Vec<f32>> = OnceLock::new();
+    static HLG: OnceLock<Vec<f32>> = OnceLock::new();
+    let build = |to_nits: fn(f32) -> f32| {
+        (0..=u16::MAX)
+            .map(|code| to_nits(code as f32 / u16::MAX as f32) / SDR_WHITE_NITS)
+            .collect::<Vec<_>>()
+    };
+    match transfer {
+        VideoTransfer::Hlg => HLG.get_or_init(|| build(hlg_to_nits)),
+        _ => PQ.get_or_init(|| build(pq_to_nits)),
+    }
+}
+
+/// sRGB encoded byte for linear values in [0, 1], sampled at 4096 steps.
+fn srgb_lut() -> &'static [u8] {
+    static SRGB: OnceLock<Vec<u8>> = OnceLock::new();
+    SRGB.get_or_init(|| {
+        (0..4096)
+            .map(|step| {
+                let linear = step as f32 / 4095.0;
+                let encoded = if linear <= 0.003_130_8 {
+                    linear * 12.92
+                } else {
+                    1.055 * linear.powf(1.0 / 2.4) - 0.055
+                };
+                (encoded * 255.0).round() as u8
+            })
+            .collect()
+    })
+}
+
+fn reinhard(value: f32) -> f32 {
+    let white = PEAK_NITS / SDR_WHITE_NITS;
+    value * (1.0 + value / (white * white)) / (1.0 + value)
+}
+
+fn read_u16(data: &[u8], index: usize) -> u16 {
+    u16::from_le_bytes([data[index], data[index + 1]])
+}
+
+/// Converts little-endian RGBA64 HDR samples into 8-bit sRGB RGBA.
+pub fn tonemap_rgba64(data: &[u8], transfer: VideoTransfer) -> Vec<u8> {
+    let linear = linear_lut(transfer);
+    let srgb = srgb_lut();
+    let mut out = Vec::with_capacity(data.len() / 2);
+    for pixel in data.chunks_exact(8) {
+        let rgb = [
+            linear[read_u16(pixel, 0) as usize],
+            linear[read_u16(pixel, 2) as usize],
+            linear[read_u16(pixel, 4) as usize],
+        ];
+        for row in BT2020_TO_BT709.iter() {
+            let value = row[0] * rgb[0] + row[1] * rgb[1] + row[2] * rgb[2];
+            let mapped = reinhard(value.max(0.0)).min(1.0);
+            out.push(srgb[(mapped * 4095.0) as usize]);
+        }
+        out.push(pixel[7]);
+    }
+    out
+}
+
+/// Keeps the high byte of every little-endian 16-bit SDR sample.
+pub fn rgba64_to_rgba8(data: &[u8]) -> Vec<u8> {
+    data.chunks_exact(2).map(|sample| sample[1]).collect()
+}
+
+/// Builds the texture for a 16-bit frame according to `mode`.
+pub fn rgba64_to_image(
+    width: u32,
+    height: u32,
+    data: Vec<u8>,
+    transfer: VideoTransfer,
+    mode: HdrMode,
+) -> Option<Image> {
+    if data.len() != width as usize * height as usize * 8 {
+        return None;
+    }
+    let size = Extent3d {
+        width,
+        height,
+        ..default()
+    };
+    let image = match mode {
+        HdrMode::Passthrough => Image::new(
+            size,
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba16Unorm,
+            RenderAssetUsages::default(),
+        ),
+        HdrMode::Tonemap => {
+            let rgba8 = if transfer.is_hdr() {
+                tonemap_rgba64(&data, transfer)
+            } else {
+                rgba64_to_rgba8(&data)
+            };
+            Image::new(
+                size,
+                TextureDimension::D2,
+                rgba8,
+                TextureFormat::Rgba8UnormSrgb,
+                RenderAssetUsages::default(),
+            )
+        }
+    };
+    Some(image)
+}
@@ -0,0 +1,33 @@
+use bevy::{
+    prelude::*,
+    window::{WindowFocused, WindowOccluded},
+};
+
+use crate::plugin::{render_video_frame, VideoPlayer, VideoPlugin};
+
+/// App running `VideoPlugin` and `render_video_frame` without a window, GPU
+/// or audio output, for integration tests. Drive it with `App::update`;
+/// frames land in `Assets<Image>` and can be read back with
+/// `VideoPlayer::snapshot`. Use silent sources such as `VideoSource::test`,
+/// since audio needs an output device.
+pub fn headless_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+        .init_asset::<Image>()
+        .init_resource::<UiScale>()
+        .add_event::<WindowFocused>()
+        .add_event::<WindowOccluded>()
+        .add_plugins(VideoPlugin)
+        .add_systems(Update, render_video_frame);
+    app
+}
+
+/// Spawns `video_player` with only what `render_video_frame` needs.
+pub fn spawn_headless_player(world: &mut World, video_player: VideoPlayer) -> Entity {
+    let entity = world.spawn(UiImage::default()).id();
+    world.entity_mut(entity).insert(VideoPlayer {
+        id: Some(entity),
+        ..video_player
+    });
+    entity
+}
@@ -0,0 +1,243 @@
+extern crate gstreamer as gst;
+
+use std::sync::{Arc, Mutex};
+
+use bevy::prelude::*;
+use gst::prelude::*;
+
+/// Most bytes of a master playlist that are kept for parsing.
+const MAX_PLAYLIST_BYTES: usize = 1024 * 1024;
+
+/// Quality level of an HLS stream, from its master playlist.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HlsVariant {
+    /// Peak bitrate in bits per second.
+    pub bandwidth: u64,
+    pub resolution: Option<UVec2>,
+    pub frame_rate: Option<f32>,
+    /// RFC 6381 codec list, e.g. `"avc1.64001f,mp4a.40.2"`.
+    pub codecs: Option<String>,
+    pub uri: String,
+}
+
+/// Which variants of an HLS stream the demuxer may pick from. Pinning and
+/// caps are applied through the demuxer's bitrate limits, so they also
+/// hold when it would otherwise switch up.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Reflect)]
+pub enum VariantSelection {
+    /// Follow the measured connection speed.
+    #[default]
+    Auto,
+    /// Always play the variant at this index of `HlsVariant`s.
+    Pinned(usize),
+    /// Adapt, but never above this bitrate in bits per second or this
+    /// resolution, e.g. to cap data usage on metered connections.
+    Capped {
+        max_bitrate: Option<u64>,
+        max_resolution: Option<UVec2>,
+    },
+}
+
+/// Variants seen so far and the demuxer that switches between them.
+#[derive(Default)]
+pub(crate) struct HlsState {
+    playlist: Vec<u8>,
+    variants: Vec<HlsVariant>,
+    demuxer: Option<gst::Element>,
+    selection: VariantSelection,
+}
+
+impl HlsState {
+    pub(crate) fn variants(&self) -> Vec<HlsVariant> {
+        self.variants.clone()
+    }
+
+    pub(crate) fn set_selection(&mut self, selection: VariantSelection) {
+        self.selection = selection;
+        self.apply();
+    }
+
+    /// Bitrate range in bits per second the selection allows; 0 leaves a
+    /// bound open.
+    fn bitrate_range(&self) -> (u64, u64) {
+        match self.selection {
+            VariantSelection::Auto => (0, 0),
+            VariantSelection::Pinned(index) => match self.variants.get(index) {
+                Some(variant) => (variant.bandwidth, variant.bandwidth),
+                None => (0, 0),
+            },
+            VariantSelection::Capped {
+                max_bitrate,
+                max_resolution,
+            } => {
+                // The demuxer only knows bitrates; a resolution becomes the
+                // bitrate of the best variant that fits.
+                let resolution_cap = max_resolution.and_then(|max| {
+                    self.variants
+                        .iter()
+                        .filter(|variant| {
+                            variant
+                                .resolution
+                                .is_some_and(|size| size.x <= max.x && size.y <= max.y)
+                        })
+                        .map(|variant| variant.bandwidth)
+                        .max()
+                });
+                let cap = match (max_bitrate, resolution_cap) {
+                    (Some(bitrate), Some(resolution)) => bitrate.min(resolution),
+                    (bitrate, resolution) => bitrate.or(resolution).unwrap_or(0),
+                };
+                (0, cap)
+            }
+        }
+    }
+
+    fn apply(&self) {
+        let Some(demuxer) = self.demuxer.as_ref() else {
+            return;
+        };
+        let (min, max) = self.bitrate_range();
+        if demuxer.has_property("max-bitrate", None) {
+            set_uint(demuxer, "min-bitrate", min);
+            set_uint(demuxer, "max-bitrate", max);
+        } else {
+            // Older demuxers assume this speed instead of measuring it,
+            // which caps them just as well.
+            set_uint(demuxer, "connection-speed", max / 1000);
+        }
+    }
+
+    fn push_playlist(&mut self, data: &[u8]) -> bool {
+        self.playlist.extend_from_slice(data);
+        if !self
+            .playlist
+            .starts_with(&b"#EXTM3U"[..self.playlist.len().min(7)])
+        {
+            self.playlist = Vec::new();
+            return false;
+        }
+        let variants = parse_master_playlist(&String::from_utf8_lossy(&self.playlist));
+        if !variants.is_empty() && variants != self.variants {
+            self.variants = variants;
+            self.apply();
+        }
+        self.playlist.len() < MAX_PLAYLIST_BYTES
+    }
+}
+
+/// Sets the unsigned integer property `name`, if the element has one.
+fn set_uint(element: &gst::Element, name: &str, value: u64) {
+    let Some(spec) = element.find_property(name) else {
+        return;
+    };
+    if spec.value_type() == u32::static_type() {
+        element.set_property(name, value.min(u32::MAX as u64) as u32);
+    } else if spec.value_type() == u64::static_type() {
+        element.set_property(name, value);
+    }
+}
+
+/// Variants listed in the `#EXT-X-STREAM-INF` tags of a master playlist,
+/// in playlist order. Empty for media playlists.
+pub fn parse_master_playlist(playlist: &str) -> Vec<HlsVariant> {
+    let mut variants = Vec::new();
+    let mut lines = playlist.lines().map(str::trim);
+    while let Some(line) = lines.next() {
+        let Some(attributes) = line.strip_prefix("#EXT-X-STREAM-INF:") else {
+            continue;
+        };
+        let Some(uri) = lines.find(|line| !line.is_empty() && !line.starts_with('#')) else {
+            break;
+        };
+        let mut variant = HlsVariant {
+            bandwidth: 0,
+            resolution: None,
+            frame_rate: None,
+            codecs: None,
+            uri: uri.to_string(),
+        };
+        for (key, value) in split_attributes(attributes) {
+            match key {
+                "BANDWIDTH" => variant.bandwidth = value.parse().unwrap_or(0),
+                "RESOLUTION" => {
+                    variant.resolution = value
+                        .split_once('x')
+                        .and_then(|(w, h)| Some(UVec2::new(w.parse().ok()?, h.parse().ok()?)));
+                }
+                "FRAME-RATE" => variant.frame_rate = value.parse().ok(),
+                "CODECS" => variant.codecs = Some(value.trim_matches('"').to_string()),
+                _ => {}
+            }
+        }
+        variants.push(variant);
+    }
+    variants
+}
+
+/// Splits `KEY=value,KEY="quoted, value"` attribute lists.
+fn split_attributes(attributes: &str) -> Vec<(&str, &str)> {
+    let mut pairs = Vec::new();
+    let mut start = 0;
+    let mut quoted = false;
+    for (index, c) in attributes.char_indices().chain([(attributes.len(), ',')]) {
+        match c {
+            '"' => quoted = !quoted,
+            ',' if !quoted => {
+                if let Some((key, value)) = attributes[start..index].split_once('=') {
+                    pairs.push((key.trim(), value.trim()));
+                }
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    pairs
+}
+
+/// Collects the master playlist as uridecodebin's source downloads it, and
+/// the HLS demuxer once it is plugged, into `state`.
+pub(crate) fn watch_hls(pipeline: &gst::Pipeline, state: &Arc<Mutex<HlsState>>) {
+    let Some(decodebin) = pipeline.by_name("decodebin") else {
+        return;
+    };
+    if !decodebin.has_property("buffer-duration", None) {
+        return;
+    }
+    let playlist_state = Arc::clone(state);
+    decodebin.connect("source-setup", false, move |values| {
+        let pad = values[1]
+            .get::<gst::Element>()
+            .ok()
+            .and_then(|source| source.static_pad("src"))?;
+        let state = Arc::clone(&playlist_state);
+        pad.add_probe(gst::PadProbeType::BUFFER, move |_, info| {
+            let Some(buffer) = info.buffer() else {
+                return gst::PadProbeReturn::Ok;
+            };
+            let Ok(map) = buffer.map_readable() else {
+                return gst::PadProbeReturn::Ok;
+            };
+            let keep = state
+                .lock()
+                .is_ok_and(|mut state| state.push_playlist(map.as_slice()));
+            if keep {
+                gst::PadProbeReturn::Ok
+            } else {
+                gst::PadProbeReturn::Remove
+            }
+        });
+        None
+    });
+    let demuxer_state = Arc::clone(state);
+    pipeline.connect_deep_element_added(move |_, _, element| {
+        let is_hls = element
+            .factory()
+            .is_some_and(|factory| factory.name().starts_with("hlsdemux"));
+        if is_hls {
+            if let Ok(mut state) = demuxer_state.lock() {
+                state.demuxer = Some(element.clone());
+                state.apply();
+            }
+        }
+    });
+}
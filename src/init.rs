@@ -0,0 +1,111 @@
+extern crate gstreamer as gst;
+
+use std::{path::PathBuf, sync::OnceLock};
+
+use bevy::{log::debug, reflect::Reflect};
+use gst::prelude::*;
+
+/// How GStreamer is initialized. Only the first initialization counts:
+/// `VideoPlugin` applies `VideoPluginConfig::gstreamer` when it is added,
+/// so insert the config before the plugin.
+///
+/// Some settings GStreamer only reads from the environment, which the
+/// plugin leaves alone. Set them at the start of `main`, before any thread
+/// is spawned, if needed:
+/// - `GST_REGISTRY_UPDATE=no` skips rescanning the system plugin
+///   directories on startup.
+/// - `GST_REGISTRY` and `TMPDIR` (`TMP`/`TEMP` on Windows) point the
+///   registry and temporary files at a writable directory, e.g. the app's
+///   cache directory on Android.
+/// - `HOME`, `XDG_CACHE_HOME`, `XDG_DATA_HOME` and `XDG_CONFIG_HOME`, where
+///   the platform provides none.
+#[derive(Debug, Clone, Reflect)]
+pub struct GstInitOptions {
+    /// Extra directories scanned for plugins, e.g. plugins shipped next to
+    /// the executable.
+    pub plugin_paths: Vec<PathBuf>,
+    /// Debug thresholds in `GST_DEBUG` syntax, e.g. `"2,souphttpsrc:5"`.
+    /// Replaces the levels set through the environment.
+    pub debug: Option<String>,
+    /// Called right after `gst::init` to register statically linked
+    /// plugins, e.g. `gst_init_static_plugins` from the
+    /// `gstreamer_android.c` generated by the Android NDK build.
+    #[reflect(ignore)]
+    pub register_static_plugins: Option<fn()>,
+    /// Name prefixes of decoders that decodebin should try before any
+    /// other, most preferred first; typically hardware decoders. Defaults
+    /// to the MediaCodec decoders (`amcviddec-`) on Android and to
+    /// VideoToolbox (`vtdec_hw`, then `vtdec` which may fall back to
+    /// software) on macOS and iOS.
+    pub preferred_decoders: Vec<String>,
+}
+
+impl Default for GstInitOptions {
+    fn default() -> Self {
+        let preferred_decoders = if cfg!(target_os = "android") {
+            vec!["amcviddec-".to_string()]
+        } else if cfg!(any(target_os = "macos", target_os = "ios")) {
+            vec!["vtdec_hw".to_string(), "vtdec".to_string()]
+        } else {
+            Vec::new()
+        };
+        GstInitOptions {
+            plugin_paths: Vec::new(),
+            debug: None,
+            register_static_plugins: None,
+            preferred_decoders,
+        }
+    }
+}
+
+static INITIALIZED: OnceLock<Result<(), String>> = OnceLock::new();
+
+/// Initializes GStreamer with `options` on the first call; later calls
+/// return the first call's result and ignore their options.
+pub fn init_gstreamer(options: &GstInitOptions) -> Result<(), String> {
+    INITIALIZED
+        .get_or_init(|| {
+            gst::init().map_err(|err| err.to_string())?;
+            if let Some(register_static_plugins) = options.register_static_plugins {
+                register_static_plugins();
+            }
+            let registry = gst::Registry::get();
+            for path in &options.plugin_paths {
+                if !registry.scan_path(path) {
+                    debug!("No new GStreamer plugins in {}", path.display());
+                }
+            }
+            if let Some(debug) = &options.debug {
+                gst::log::set_threshold_from_string(debug, true);
+            }
+            prefer_decoders(&options.preferred_decoders);
+            Ok(())
+        })
+        .clone()
+}
+
+/// Ranks the decoders matching `prefixes` above every other decoder, in
+/// the order of their first matching prefix, so decodebin plugs them first
+/// and falls back to the rest if they fail.
+fn prefer_decoders(prefixes: &[String]) {
+    if prefixes.is_empty() {
+        return;
+    }
+    let decoders =
+        gst::ElementFactory::factories_with_type(gst::ElementFactoryType::DECODER, gst::Rank::NONE);
+    for factory in decoders.iter() {
+        let name = factory.name();
+        if let Some(position) = prefixes
+            .iter()
+            .position(|prefix| name.starts_with(prefix.as_str()))
+        {
+            factory.set_rank(gst::Rank::PRIMARY + (prefixes.len() - position) as i32);
+        }
+    }
+}
+
+/// Initializes GStreamer with the default options unless that already
+/// happened, for pipelines that may be built without `VideoPlugin`.
+pub(crate) fn ensure_gstreamer() -> Result<(), String> {
+    init_gstreamer(&GstInitOptions::default())
+}
@@ -0,0 +1,35 @@
+pub mod audio;
+pub mod backend;
+pub mod calibration;
+pub mod color;
+pub mod controls;
+pub mod devices;
+pub mod diagnostics;
+pub mod dirty;
+pub mod gif;
+pub mod hdr;
+pub mod headless;
+pub mod hls;
+pub mod init;
+pub mod missing;
+pub mod orientation;
+pub mod panorama;
+pub mod picking;
+pub mod playlist;
+pub mod plugin;
+pub mod pool;
+pub mod projector;
+pub mod record;
+pub mod scene;
+pub mod screensaver;
+pub mod shared;
+pub mod shortcuts;
+pub mod surface;
+#[cfg(feature = "test-media")]
+pub mod test_support;
+pub mod texture;
+pub mod thumbnail;
+pub mod transcode;
+pub mod usage;
+pub mod video;
+pub mod worker;
@@ -1,96 +1,61 @@
-use std::sync::{Arc, Mutex};
-
-use bevy::prelude::*;
-use plugin::{insert_video_component, VideoPlayer, VideoState};
-mod plugin;
-mod video;
+use bevy::{prelude::*, window::PrimaryWindow};
+use bevy_gst_video::{
+    controls::VideoControlsBundle,
+    plugin::{self, insert_video_component_scaled, ui_scale_factor, ReconnectPolicy, VideoPlayer},
+    shortcuts::VideoShortcuts,
+};
 
 fn main() {
     App::new()
         .add_plugins((DefaultPlugins, plugin::VideoPlugin))
+        .init_resource::<VideoShortcuts>()
         .add_systems(Startup, start_up)
         .add_systems(Update, (update, plugin::render_video_frame))
         .run();
 }
 
-fn start_up(mut commands: Commands, images: ResMut<Assets<Image>>, asset_server: Res<AssetServer>) {
+fn start_up(
+    mut commands: Commands,
+    images: ResMut<Assets<Image>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    ui_scale: Res<UiScale>,
+) {
     commands.spawn((Camera2dBundle::default(), IsDefaultUiCamera));
     let uri = "https://gstreamer.freedesktop.org/data/media/sintel_trailer-480p.webm";
     let video_player = VideoPlayer {
-        uri: uri.to_string(),
-        state: VideoState::Init,
-        timer: Arc::new(Mutex::new(Timer::from_seconds(1.0, TimerMode::Repeating))),
+        source: uri.into(),
         width: 500.0,
         height: 500.0,
-        id: None,
-        pipeline: None,
+        pause_on_buffering: true,
+        reconnect: Some(ReconnectPolicy::default()),
+        ..Default::default()
     };
-    commands
-        .spawn(insert_video_component(
+    let player = commands
+        .spawn(insert_video_component_scaled(
             images,
             Vec2::new(video_player.width, video_player.height),
+            ui_scale_factor(windows.get_single().ok(), &ui_scale),
         ))
-        .insert(video_player);
+        .insert(video_player)
+        .id();
 
     commands
         .spawn(NodeBundle {
             style: Style {
-                top: Val::Px(550.0),
-                left: Val::Px(200.0),
+                position_type: PositionType::Absolute,
+                top: Val::Px(510.0),
+                width: Val::Px(500.0),
                 ..Default::default()
             },
             ..Default::default()
         })
         .with_children(|parent| {
-            parent
-                .spawn(TextBundle::from_section(
-                    "start    ",
-                    TextStyle {
-                        font: asset_server.load("fonts/FiraMono-Medium.ttf"),
-                        font_size: 12.0,
-                        color: Color::WHITE,
-                        ..Default::default()
-                    },
-                ))
-                .insert(Interaction::Pressed);
-            parent
-                .spawn(TextBundle::from_section(
-                    "   stop",
-                    TextStyle {
-                        font: asset_server.load("fonts/FiraMono-Medium.ttf"),
-                        font_size: 12.0,
-                        color: Color::WHITE,
-                        ..Default::default()
-                    },
-                ))
-                .insert(Interaction::Pressed);
+            parent.spawn(VideoControlsBundle::new(player));
         });
 }
 
-fn update(
-    mut query: Query<(&Interaction, &mut Text)>,
-    mut query_video: Query<(&mut VideoPlayer, Entity, &mut UiImage)>,
-) {
-    for (mut video_player, id, _) in query_video.iter_mut() {
-        for (interaction, text) in query.iter_mut() {
-            match interaction {
-                Interaction::Pressed => {
-                    if video_player.id.is_some() {
-                        match text.sections[0].value.trim() {
-                            "start" => {
-                                video_player.state = VideoState::Start;
-                            }
-                            "stop" => {
-                                video_player.state = VideoState::Paused;
-                            }
-                            _ => {}
-                        }
-                    }
-                }
-                _ => {}
-            }
-        }
-
+fn update(mut query_video: Query<(&mut VideoPlayer, Entity)>) {
+    for (mut video_player, id) in query_video.iter_mut() {
         if video_player.id.is_none() {
             video_player.id = Some(id);
         }
@@ -0,0 +1,92 @@
+extern crate gstreamer as gst;
+
+/// GStreamer module that ships the element, or handles the media type, in
+/// `key`. Defaults to gst-plugins-bad, where most codecs start out.
+fn plugin_module(key: &str) -> &'static str {
+    const BASE: &[&str] = &[
+        "appsink",
+        "appsrc",
+        "audioconvert",
+        "audioresample",
+        "audiotestsrc",
+        "decodebin",
+        "oggdemux",
+        "opusdec",
+        "playbin",
+        "theoradec",
+        "uridecodebin",
+        "videoconvert",
+        "videorate",
+        "videoscale",
+        "videotestsrc",
+        "vorbisdec",
+        "audio/x-opus",
+        "audio/x-vorbis",
+        "video/x-theora",
+        "application/ogg",
+    ];
+    const GOOD: &[&str] = &[
+        "equalizer-10bands",
+        "flacdec",
+        "jpegdec",
+        "matroskademux",
+        "mp4mux",
+        "pngdec",
+        "qtdemux",
+        "rtspsrc",
+        "scaletempo",
+        "souphttpsrc",
+        "v4l2src",
+        "videoflip",
+        "vp8dec",
+        "vp9dec",
+        "audio/x-flac",
+        "image/jpeg",
+        "video/quicktime",
+        "video/x-matroska",
+        "video/x-vp8",
+        "video/x-vp9",
+    ];
+    const UGLY: &[&str] = &["a52dec", "mpeg2dec", "x264enc", "video/mpeg"];
+    const LIBAV: &[&str] = &["audio/mpeg", "video/x-h264", "video/x-h265"];
+    if key.starts_with("av") && key.contains('_') || LIBAV.contains(&key) {
+        "gst-libav"
+    } else if BASE.contains(&key) {
+        "gst-plugins-base"
+    } else if GOOD.contains(&key) {
+        "gst-plugins-good"
+    } else if UGLY.contains(&key) {
+        "gst-plugins-ugly"
+    } else {
+        "gst-plugins-bad"
+    }
+}
+
+/// What to install for the element or media type `key`, e.g. `"avdec_h264"`
+/// or `"video/x-h265"`, in a form that can be shown to the user.
+pub fn install_hint(key: &str) -> String {
+    let module = plugin_module(key);
+    let package = module.replacen("gst-", "gstreamer1.0-", 1);
+    format!("Install {module} ({package} on Debian and Ubuntu)")
+}
+
+/// Name and install hint from a `missing-plugin` element message, as posted
+/// by decodebin when it finds no decoder for a stream.
+pub(crate) fn parse_missing_plugin(structure: &gst::StructureRef) -> Option<(String, String)> {
+    if structure.name() != "missing-plugin" {
+        return None;
+    }
+    let detail = match structure.get::<gst::Caps>("detail") {
+        Ok(caps) => caps.structure(0)?.name().to_string(),
+        Err(_) => structure.get::<String>("detail").ok()?,
+    };
+    let name = structure
+        .get::<String>("name")
+        .unwrap_or_else(|_| detail.clone());
+    Some((name, install_hint(&detail)))
+}
+
+/// Whether `err` means a plugin is missing rather than the stream failing.
+pub(crate) fn is_missing_plugin_error(err: &gst::glib::Error) -> bool {
+    err.matches(gst::CoreError::MissingPlugin) || err.matches(gst::StreamError::CodecNotFound)
+}
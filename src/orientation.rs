@@ -0,0 +1,56 @@
+extern crate gstreamer as gst;
+
+use std::sync::{Arc, Mutex};
+
+use gst::prelude::*;
+
+/// Orientation from the `image-orientation` tag, e.g. of phone recordings.
+/// The flip variants mirror horizontally before rotating clockwise. This is
+/// informational only: `videoflip video-direction=auto` already rotates the
+/// frames, so their width and height are final.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VideoOrientation {
+    #[default]
+    Identity,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    Flip,
+    FlipRotate90,
+    FlipRotate180,
+    FlipRotate270,
+}
+
+impl VideoOrientation {
+    pub fn from_tag(tag: &str) -> Self {
+        match tag {
+            "rotate-90" => VideoOrientation::Rotate90,
+            "rotate-180" => VideoOrientation::Rotate180,
+            "rotate-270" => VideoOrientation::Rotate270,
+            "flip-rotate-0" => VideoOrientation::Flip,
+            "flip-rotate-90" => VideoOrientation::FlipRotate90,
+            "flip-rotate-180" => VideoOrientation::FlipRotate180,
+            "flip-rotate-270" => VideoOrientation::FlipRotate270,
+            _ => VideoOrientation::Identity,
+        }
+    }
+}
+
+/// Tracks `image-orientation` tags arriving at `element`'s sink pad.
+pub fn watch_orientation(element: &gst::Element, orientation: Arc<Mutex<VideoOrientation>>) {
+    let Some(pad) = element.static_pad("sink") else {
+        return;
+    };
+    pad.add_probe(gst::PadProbeType::EVENT_DOWNSTREAM, move |_, probe_info| {
+        if let Some(gst::PadProbeData::Event(event)) = probe_info.data.as_ref() {
+            if let gst::EventView::Tag(tag) = event.view() {
+                if let Some(value) = tag.tag().get::<gst::tags::ImageOrientation>() {
+                    if let Ok(mut orientation) = orientation.lock() {
+                        *orientation = VideoOrientation::from_tag(value.get());
+                    }
+                }
+            }
+        }
+        gst::PadProbeReturn::Ok
+    });
+}
@@ -0,0 +1,164 @@
+use std::f32::consts::{PI, TAU};
+
+use bevy::{
+    pbr::{NotShadowCaster, NotShadowReceiver},
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology},
+        render_asset::RenderAssetUsages,
+    },
+};
+
+use crate::{
+    plugin::VideoPlayer,
+    surface::{spawn_offscreen_player, VideoMeshMaterial},
+};
+
+/// Inward-facing Y-up sphere mapping an equirectangular frame onto its
+/// inside. The middle of the frame faces -Z. The seam column is duplicated
+/// with u = 0 and u = 1, so the texture does not wrap across it.
+pub fn panorama_sphere(radius: f32, sectors: u32, stacks: u32) -> Mesh {
+    let (sectors, stacks) = (sectors.max(3), stacks.max(2));
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+    for stack in 0..=stacks {
+        let v = stack as f32 / stacks as f32;
+        let (ring, y) = (PI * v).sin_cos();
+        for sector in 0..=sectors {
+            let u = sector as f32 / sectors as f32;
+            let (sin, cos) = (TAU * u).sin_cos();
+            // u grows to the right as seen from the center.
+            let direction = Vec3::new(-ring * sin, y, ring * cos);
+            positions.push((direction * radius).to_array());
+            normals.push((-direction).to_array());
+            uvs.push([u, v]);
+        }
+    }
+    let mut indices = Vec::new();
+    let row = sectors + 1;
+    for stack in 0..stacks {
+        for sector in 0..sectors {
+            let top = stack * row + sector;
+            let bottom = top + row;
+            // Counter-clockwise seen from inside.
+            indices.extend([top, bottom, top + 1, top + 1, bottom, bottom + 1]);
+        }
+    }
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_indices(Indices::U32(indices))
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+}
+
+/// Spawns a player for a 360° equirectangular video and a sphere around
+/// `center` showing it, viewed by placing the camera at the center and
+/// rotating it. Returns the player and the sphere.
+pub fn spawn_video_panorama(
+    commands: &mut Commands,
+    images: ResMut<Assets<Image>>,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    video_player: VideoPlayer,
+    center: Vec3,
+    radius: f32,
+) -> (Entity, Entity) {
+    let player = spawn_offscreen_player(commands, images, video_player);
+    let sphere = commands
+        .spawn((
+            PbrBundle {
+                mesh: meshes.add(panorama_sphere(radius, 64, 32)),
+                material: materials.add(StandardMaterial {
+                    unlit: true,
+                    ..default()
+                }),
+                transform: Transform::from_translation(center),
+                ..default()
+            },
+            VideoMeshMaterial { player },
+        ))
+        .id();
+    (player, sphere)
+}
+
+/// Shows a player's equirectangular frames behind everything the camera it
+/// is on sees, replacing a static skybox with animated sky footage. A large
+/// unlit sphere follows the camera's position; keep `radius` inside the
+/// camera's far plane; it is read when the sphere is spawned.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct VideoSkybox {
+    pub player: Entity,
+    pub radius: f32,
+}
+
+impl VideoSkybox {
+    pub fn new(player: Entity) -> Self {
+        VideoSkybox {
+            player,
+            radius: 900.0,
+        }
+    }
+}
+
+/// Sphere spawned for the `VideoSkybox` on `camera`.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct VideoSkyboxSphere {
+    pub camera: Entity,
+}
+
+/// Spawns, moves and despawns the spheres of `VideoSkybox` cameras.
+pub fn update_video_skyboxes(
+    mut commands: Commands,
+    cameras: Query<(Entity, &VideoSkybox, &GlobalTransform)>,
+    mut spheres: Query<(
+        Entity,
+        &VideoSkyboxSphere,
+        &mut VideoMeshMaterial,
+        &mut Transform,
+    )>,
+    meshes: Option<ResMut<Assets<Mesh>>>,
+    materials: Option<ResMut<Assets<StandardMaterial>>>,
+) {
+    let (Some(mut meshes), Some(mut materials)) = (meshes, materials) else {
+        return;
+    };
+    let mut placed = Vec::new();
+    for (entity, sphere, mut surface, mut transform) in spheres.iter_mut() {
+        let Ok((_, skybox, camera)) = cameras.get(sphere.camera) else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+        surface.player = skybox.player;
+        transform.translation = camera.translation();
+        placed.push(sphere.camera);
+    }
+    for (camera_entity, skybox, camera) in cameras.iter() {
+        if placed.contains(&camera_entity) {
+            continue;
+        }
+        commands.spawn((
+            PbrBundle {
+                mesh: meshes.add(panorama_sphere(skybox.radius, 64, 32)),
+                material: materials.add(StandardMaterial {
+                    unlit: true,
+                    fog_enabled: false,
+                    ..default()
+                }),
+                transform: Transform::from_translation(camera.translation()),
+                ..default()
+            },
+            NotShadowCaster,
+            NotShadowReceiver,
+            VideoMeshMaterial {
+                player: skybox.player,
+            },
+            VideoSkyboxSphere {
+                camera: camera_entity,
+            },
+        ));
+    }
+}
@@ -0,0 +1,141 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::plugin::{VideoPlayer, VideoState};
+
+/// Cursor movement across the surface, as a fraction of its width, that
+/// turns a click into a scrub.
+const DRAG_THRESHOLD: f32 = 0.01;
+
+/// Makes a video quad in the 3D scene clickable: a click toggles
+/// play/pause of `player` and dragging along it scrubs, for in-world
+/// screens. The quad lies in the entity's local XY plane, centered on it
+/// and `size` big, like a `Rectangle` mesh.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct VideoPickable {
+    pub player: Entity,
+    pub size: Vec2,
+}
+
+/// Press on a pickable surface that has not been released yet.
+#[derive(Debug, Clone, Copy)]
+pub struct VideoPickDrag {
+    surface: Entity,
+    start: f32,
+    scrubbing: bool,
+    last_seek: f32,
+}
+
+/// Where the ray hits the surface's plane, in the surface's local space,
+/// and how far along the ray.
+fn hit_plane(ray: Ray3d, transform: &GlobalTransform) -> Option<(Vec2, f32)> {
+    let normal = transform.back();
+    let denom = ray.direction.dot(normal);
+    if denom.abs() < f32::EPSILON {
+        return None;
+    }
+    let distance = (transform.translation() - ray.origin).dot(normal) / denom;
+    if distance < 0.0 {
+        return None;
+    }
+    let local = transform
+        .affine()
+        .inverse()
+        .transform_point3(ray.get_point(distance));
+    Some((local.truncate(), distance))
+}
+
+/// Horizontal position of a local point on the surface, from 0 at the left
+/// edge to 1 at the right.
+fn surface_fraction(surface: &VideoPickable, local: Vec2) -> f32 {
+    local.x / surface.size.x.max(f32::EPSILON) + 0.5
+}
+
+/// Toggles and scrubs players through their `VideoPickable` surfaces with
+/// the left mouse button, using the highest-order active camera.
+pub fn pick_video_surfaces(
+    mouse: Option<Res<ButtonInput<MouseButton>>>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    surfaces: Query<(Entity, &VideoPickable, &GlobalTransform)>,
+    mut players: Query<&mut VideoPlayer>,
+    mut drag: Local<Option<VideoPickDrag>>,
+) {
+    let Some(mouse) = mouse else {
+        return;
+    };
+    let ray = window
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+        .and_then(|cursor| {
+            let (camera, transform) = cameras
+                .iter()
+                .filter(|(camera, _)| camera.is_active)
+                .max_by_key(|(camera, _)| camera.order)?;
+            camera.viewport_to_world(transform, cursor)
+        });
+
+    if mouse.just_pressed(MouseButton::Left) {
+        *drag = ray.and_then(|ray| {
+            surfaces
+                .iter()
+                .filter_map(|(entity, surface, transform)| {
+                    let (local, distance) = hit_plane(ray, transform)?;
+                    let inside = local.x.abs() <= surface.size.x / 2.0
+                        && local.y.abs() <= surface.size.y / 2.0;
+                    inside.then(|| (entity, surface_fraction(surface, local), distance))
+                })
+                .min_by(|a, b| a.2.total_cmp(&b.2))
+                .map(|(surface, start, _)| VideoPickDrag {
+                    surface,
+                    start,
+                    scrubbing: false,
+                    last_seek: start,
+                })
+        });
+        return;
+    }
+
+    let Some(mut current) = *drag else {
+        return;
+    };
+    let Ok((_, surface, transform)) = surfaces.get(current.surface) else {
+        *drag = None;
+        return;
+    };
+    let Ok(mut video_player) = players.get_mut(surface.player) else {
+        *drag = None;
+        return;
+    };
+
+    if mouse.just_released(MouseButton::Left) {
+        if !current.scrubbing {
+            if video_player.state() == VideoState::Playing {
+                video_player.pause();
+            } else {
+                video_player.play();
+            }
+        }
+        *drag = None;
+        return;
+    }
+
+    // Past the edges the drag keeps following the surface's plane.
+    let Some(position) = ray
+        .and_then(|ray| hit_plane(ray, transform))
+        .map(|(local, _)| surface_fraction(surface, local).clamp(0.0, 1.0))
+    else {
+        return;
+    };
+    if !current.scrubbing && (position - current.start).abs() > DRAG_THRESHOLD {
+        current.scrubbing = true;
+    }
+    if current.scrubbing && (position - current.last_seek).abs() > f32::EPSILON {
+        let duration = video_player.share().and_then(|shared| shared.duration());
+        if let Some(duration) = duration {
+            video_player.seek(duration.mul_f32(position));
+            current.last_seek = position;
+        }
+    }
+    *drag = Some(current);
+}
@@ -0,0 +1,205 @@
+use bevy::prelude::*;
+
+use crate::{
+    plugin::{build_pipeline, install_pipeline, VideoEnded, VideoPlayer, VideoState},
+    video::{GstPlayer, VideoSource},
+    worker::VideoWorkers,
+};
+
+/// Ordered sources played one after another by the `VideoPlayer` on the
+/// same entity. The next item is prerolled while the current one plays, so
+/// the switch on end of stream shows no black frame.
+#[derive(Component)]
+pub struct VideoPlaylist {
+    pub items: Vec<VideoSource>,
+    /// Start over after the last item instead of stopping.
+    pub looping: bool,
+    /// Build the next item's pipeline ahead of time. Takes a worker slot.
+    pub preroll: bool,
+    index: usize,
+    pending: Option<usize>,
+    /// The current item ended; switch once its queued frames are shown.
+    draining: bool,
+    next: Option<(usize, GstPlayer)>,
+}
+
+/// Sent when a playlist switches to another item.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct VideoPlaylistAdvanced {
+    pub entity: Entity,
+    pub index: usize,
+}
+
+impl VideoPlaylist {
+    pub fn new(items: Vec<VideoSource>) -> Self {
+        VideoPlaylist {
+            items,
+            looping: false,
+            preroll: true,
+            index: 0,
+            pending: None,
+            draining: false,
+            next: None,
+        }
+    }
+
+    pub fn looping(mut self) -> Self {
+        self.looping = true;
+        self
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Whether another item follows the current one.
+    pub fn has_next(&self) -> bool {
+        self.following(self.index).is_some()
+    }
+
+    pub fn current(&self) -> Option<&VideoSource> {
+        self.items.get(self.index)
+    }
+
+    fn following(&self, index: usize) -> Option<usize> {
+        if index + 1 < self.items.len() {
+            Some(index + 1)
+        } else if self.looping && !self.items.is_empty() {
+            Some(0)
+        } else {
+            None
+        }
+    }
+
+    pub fn next(&mut self) {
+        self.pending = self.following(self.index);
+    }
+
+    pub fn previous(&mut self) {
+        self.pending = if self.index > 0 {
+            Some(self.index - 1)
+        } else if self.looping && !self.items.is_empty() {
+            Some(self.items.len() - 1)
+        } else {
+            None
+        };
+    }
+
+    pub fn jump_to(&mut self, index: usize) {
+        if index < self.items.len() {
+            self.pending = Some(index);
+        }
+    }
+
+    fn discard_preroll(&mut self) {
+        if let Some((_, pipeline)) = self.next.take() {
+            pipeline.shutdown();
+        }
+    }
+}
+
+impl Drop for VideoPlaylist {
+    fn drop(&mut self) {
+        self.discard_preroll();
+    }
+}
+
+/// Shows `index` on the player, with the prerolled pipeline if it is for
+/// that item or a fresh one otherwise.
+fn switch_to(video_player: &mut VideoPlayer, playlist: &mut VideoPlaylist, index: usize) {
+    let playing = video_player.state() == VideoState::Playing;
+    let previous = video_player.pipeline.take();
+    video_player.source = playlist.items[index].clone();
+    playlist.index = index;
+    playlist.draining = false;
+    match playlist.next.take() {
+        Some((next_index, pipeline)) if next_index == index => {
+            if let Some(previous) = previous.as_ref() {
+                pipeline.set_volume(previous.volume());
+            }
+            if playing {
+                pipeline.play();
+            }
+            install_pipeline(video_player, pipeline);
+        }
+        next => {
+            if let Some((_, pipeline)) = next {
+                pipeline.shutdown();
+            }
+            video_player.runtime.state = VideoState::Init;
+            if playing {
+                video_player.play();
+            }
+        }
+    }
+    if let Some(previous) = previous {
+        previous.shutdown();
+    }
+}
+
+/// Moves playlists to their next item on end of stream or on request, and
+/// prerolls the item after the current one.
+pub fn advance_video_playlists(
+    mut query: Query<(Entity, &mut VideoPlayer, &mut VideoPlaylist)>,
+    mut ended: EventReader<VideoEnded>,
+    mut advanced: EventWriter<VideoPlaylistAdvanced>,
+    mut workers: ResMut<VideoWorkers>,
+) {
+    for event in ended.read() {
+        if let Ok((_, _, mut playlist)) = query.get_mut(event.entity) {
+            if let Some(next) = playlist.following(playlist.index) {
+                playlist.draining = true;
+                // Start decoding now so frames are queued by the time the
+                // current item's last ones are shown.
+                if let Some((index, pipeline)) = playlist.next.as_ref() {
+                    if *index == next {
+                        pipeline.play();
+                    }
+                }
+            }
+        }
+    }
+
+    for (entity, mut video_player, mut playlist) in query.iter_mut() {
+        if playlist.items.is_empty() {
+            continue;
+        }
+        if playlist.index >= playlist.items.len() {
+            playlist.index = 0;
+        }
+        if video_player.pipeline.is_none() && video_player.state() == VideoState::Init {
+            video_player.source = playlist.items[playlist.index].clone();
+        }
+
+        let drained = playlist.draining
+            && video_player
+                .pipeline
+                .as_ref()
+                .map(|pipeline| pipeline.queued_frames().0 == 0)
+                .unwrap_or(true);
+        let target = match playlist.pending.take() {
+            Some(index) => Some(index),
+            None if drained => playlist.following(playlist.index),
+            None => None,
+        };
+        if let Some(index) = target {
+            switch_to(&mut video_player, &mut playlist, index);
+            advanced.send(VideoPlaylistAdvanced { entity, index });
+        }
+
+        let wanted = playlist
+            .following(playlist.index)
+            .filter(|_| playlist.preroll);
+        if playlist.next.as_ref().map(|(index, _)| *index) != wanted {
+            playlist.discard_preroll();
+        }
+        if let Some(index) = wanted {
+            if playlist.next.is_none() && video_player.pipeline.is_some() && workers.has_capacity()
+            {
+                let pipeline = build_pipeline(&video_player, &playlist.items[index]);
+                workers.spawn(entity, pipeline.control_handle());
+                playlist.next = Some((index, pipeline));
+            }
+        }
+    }
+}
@@ -1,118 +1,2029 @@
+#[cfg(feature = "audio")]
+use crate::audio::VideoAudioStream;
+#[cfg(feature = "audio")]
+use bevy::audio::{AddAudioSource, AudioPlugin};
 use bevy::{
     prelude::*,
-    render::{render_asset::RenderAssetUsages, render_resource::Extent3d},
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+    ui::CalculatedClip,
+    window::{PrimaryWindow, WindowFocused, WindowOccluded},
 };
+extern crate gstreamer as gst;
+extern crate gstreamer_video as gst_video;
+
 use image::DynamicImage;
 use std::{
-    sync::{Arc, Mutex},
-    thread,
-    time::Duration,
+    collections::{HashMap, HashSet},
+    sync::{mpsc::Receiver, Arc},
+    time::{Duration, Instant},
 };
 
-use crate::video::GstPlayer;
+use crate::{
+    audio::AudioEffects,
+    calibration::{run_av_calibration, AvCalibration},
+    color::ColorSpace,
+    controls::{
+        build_video_controls, handle_video_controls, load_scrub_previews, update_scrub_previews,
+        update_video_controls,
+    },
+    dirty::{build_dirty_regions, DirtyRegionUploads, DirtyRegions},
+    hdr::{rgba64_to_image, HdrMode, VideoTransfer},
+    hls::{HlsVariant, VariantSelection},
+    init::{init_gstreamer, GstInitOptions},
+    panorama::update_video_skyboxes,
+    picking::pick_video_surfaces,
+    playlist::{advance_video_playlists, VideoPlaylist, VideoPlaylistAdvanced},
+    pool::{schedule_video_decoding, VideoDecodePool},
+    projector::{build_video_projectors, update_video_projectors},
+    record::{capture_video_recording, VideoRecording},
+    scene::{load_video_player_configs, sync_video_player_configs, VideoPlayerConfig},
+    screensaver::{run_video_screensaver, VideoScreensaver},
+    shared::share_video_pipelines,
+    shortcuts::{video_keyboard_shortcuts, VideoShortcuts},
+    surface::sync_video_mesh_materials,
+    texture::update_video_textures,
+    transcode::{run_video_transcodes, VideoTranscodeFinished, VideoTranscodeProgress},
+    usage::{update_video_usage, VideoUsage},
+    video::{
+        FrameProcessor, GstPlayer, NetworkOptions, NetworkStats, PipelineTuning, PlayerMessage,
+        PlayerOptions, QueuePolicy, SeekPrecision, SharedVideoSource, VideoChapter, VideoInfo,
+        VideoSource,
+    },
+    worker::{
+        reap_video_workers, shutdown_removed_players, shutdown_video_workers_on_exit,
+        VideoWorkerPanicked, VideoWorkers,
+    },
+};
 
-#[derive(Debug, Clone, Copy)]
+/// Where a player is in its lifecycle, read with [`VideoPlayer::state`].
+/// `Init` and `Ready` are managed by the plugin; drive playback with
+/// `ControlVideo` events or the `VideoPlayer::play`-style methods.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
 pub enum VideoState {
     Init,
     Playing,
     Paused,
-    Start,
     Ready,
-    #[allow(dead_code)]
     Stop,
 }
 
-#[derive(Component, Clone)]
+/// Bookkeeping the plugin keeps on each player. Public only so
+/// `VideoPlayer { ..Default::default() }` works; its fields are not.
+#[derive(Debug, Reflect)]
+pub struct PlayerRuntime {
+    pub(crate) state: VideoState,
+    /// Set while the `VideoDecodePool` holds the pipeline torn down.
+    pub(crate) evicted: bool,
+    /// Requests made this frame, see [`VideoPlayer::request`].
+    #[reflect(ignore)]
+    pub(crate) commands: Vec<VideoCommand>,
+    /// Offset currently set on the pipeline, in nanoseconds.
+    pub(crate) applied_av_offset: Option<i64>,
+    /// Rate currently set on the pipeline.
+    pub(crate) applied_rate: Option<f64>,
+    /// Loop settings, effects and variant currently set on the pipeline.
+    pub(crate) applied_loop_mode: Option<LoopMode>,
+    pub(crate) applied_loop_segment: Option<(Duration, Duration)>,
+    pub(crate) applied_audio_effects: Option<AudioEffects>,
+    pub(crate) applied_variant: Option<VariantSelection>,
+    /// Counts down to the next reconnect attempt.
+    pub(crate) reconnect_timer: Option<Timer>,
+    /// Decoded frame count and the `Time::elapsed` it was last seen to
+    /// change at, while playing.
+    pub(crate) stall_watch: Option<(u64, Duration)>,
+    /// Set once `on_end` was applied, until a frame is presented again.
+    pub(crate) end_frame_shown: bool,
+    /// Set once the prerolled frame of the current pipeline is on screen.
+    pub(crate) preloaded: bool,
+    /// Set while the player is paused because the window went to the
+    /// background.
+    pub(crate) paused_in_background: bool,
+    /// Set while the player is paused by `HiddenPolicy::Pause`.
+    pub(crate) paused_while_hidden: bool,
+    /// PTS up to which cues have been sent.
+    pub(crate) cue_position: Option<Duration>,
+}
+
+impl Default for PlayerRuntime {
+    fn default() -> Self {
+        PlayerRuntime {
+            state: VideoState::Init,
+            evicted: false,
+            commands: Vec::new(),
+            applied_av_offset: None,
+            applied_rate: None,
+            applied_loop_mode: None,
+            applied_loop_segment: None,
+            applied_audio_effects: None,
+            applied_variant: None,
+            reconnect_timer: None,
+            stall_watch: None,
+            end_frame_shown: false,
+            preloaded: false,
+            paused_in_background: false,
+            paused_while_hidden: false,
+            cue_position: None,
+        }
+    }
+}
+
+/// Playback request queued on a player and applied once per frame, in the
+/// order it was made.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum VideoCommand {
+    Play,
+    Pause,
+    Stop,
+    /// Jump to a position; the playback state is kept.
+    Seek(Duration, SeekPrecision),
+}
+
+/// Queues `command` on the player on `entity`. One event type for every
+/// command, so commands sent in the same frame apply in the order sent.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct ControlVideo {
+    pub entity: Entity,
+    pub command: VideoCommand,
+}
+
+impl ControlVideo {
+    /// Starts or resumes the player.
+    pub fn play(entity: Entity) -> Self {
+        ControlVideo {
+            entity,
+            command: VideoCommand::Play,
+        }
+    }
+
+    pub fn pause(entity: Entity) -> Self {
+        ControlVideo {
+            entity,
+            command: VideoCommand::Pause,
+        }
+    }
+
+    /// Stops the player and rewinds it to the start.
+    pub fn stop(entity: Entity) -> Self {
+        ControlVideo {
+            entity,
+            command: VideoCommand::Stop,
+        }
+    }
+
+    pub fn seek(entity: Entity, position: Duration, precision: SeekPrecision) -> Self {
+        ControlVideo {
+            entity,
+            command: VideoCommand::Seek(position, precision),
+        }
+    }
+}
+
+/// Owns its pipeline, so it is not `Clone`; use [`VideoPlayer::share`] to
+/// hand out observers. Reflected for inspectors, without the pipeline and
+/// the other runtime internals.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct VideoPlayer {
-    pub state: VideoState,
-    pub timer: Arc<Mutex<Timer>>,
     pub id: Option<Entity>,
     pub width: f32,
     pub height: f32,
-    pub uri: String,
+    /// How the frame fills the `width` x `height` rect.
+    pub fit: VideoFit,
+    /// Region of the video to show, in native pixels; `None` shows the whole
+    /// frame.
+    pub crop: Option<Rect>,
+    pub source: VideoSource,
+    /// Decode `source` once for every shared player showing it; see
+    /// [`share_video_pipelines`].
+    pub shared: bool,
+    /// The shared player whose pipeline this one shows.
+    pub shared_with: Option<Entity>,
+    /// Headers, credentials and proxy settings for HTTP sources.
+    pub network: NetworkOptions,
+    #[reflect(ignore)]
     pub pipeline: Option<GstPlayer>,
+    /// Pause the pipeline while a network source refills its buffer and
+    /// resume once it reports 100%.
+    pub pause_on_buffering: bool,
+    pub buffering: bool,
+    pub reconnect: Option<ReconnectPolicy>,
+    pub reconnect_attempts: u32,
+    /// Watch for playback that stops producing frames; `None` disables it.
+    pub stall: Option<StallPolicy>,
+    pub stalled: bool,
+    pub thumbnail_scaling: Option<ThumbnailScaling>,
+    /// Frame size reported by the decoder before any downscaling, after
+    /// applying the stream's rotation tag.
+    pub native_size: Option<UVec2>,
+    /// Physical size the pipeline is currently asked to decode at, if
+    /// thumbnail scaling is active.
+    pub downscaled: Option<UVec2>,
+    /// Upload only changed rows of each frame; `None` replaces the whole
+    /// texture every frame.
+    #[reflect(ignore)]
+    pub dirty_regions: Option<DirtyRegions>,
+    /// Texture of the most recently presented frame.
+    pub current_frame: Option<Handle<Image>>,
+    /// Image shown until the first frame is presented, instead of the
+    /// blank placeholder texture, e.g. while a network source connects.
+    pub poster: Option<Handle<Image>>,
+    /// Show `poster` again when the pipeline reports an error, until frames
+    /// arrive again.
+    pub poster_on_error: bool,
+    /// What the texture shows once playback is stopped or the stream ended
+    /// and its last frame was presented.
+    pub on_end: EndFrame,
+    /// Start playing as soon as the pipeline has prerolled, instead of
+    /// waiting in `VideoState::Ready` for a play request.
+    pub autoplay: bool,
+    /// Show the first frame as a still once the pipeline has prerolled, so
+    /// the player has a picture in `VideoState::Ready` before it starts.
+    pub preload: bool,
+    /// Set once frames with a real alpha channel arrive, so materials
+    /// showing the texture can switch to alpha blending.
+    pub has_alpha: bool,
+    /// Matrix and range for YUV conversion; `Auto` follows the stream.
+    pub color_space: ColorSpace,
+    /// How 16-bit and HDR frames become textures.
+    pub hdr: HdrMode,
+    /// Transfer function of the frame on screen. With
+    /// `HdrMode::Passthrough` a custom material needs it to tonemap.
+    pub transfer: VideoTransfer,
+    /// PTS of the frame on screen and the `Time::elapsed` at which it was
+    /// presented; anchors [`VideoPlayer::pts_to_time`].
+    pub presentation_anchor: Option<(Duration, Duration)>,
+    /// CPU time the last presented frame took to convert and hand to the
+    /// renderer.
+    pub upload_time: Duration,
+    /// Lip-sync correction for this player in milliseconds, added to
+    /// `VideoPluginConfig::av_offset_ms`. Positive values delay video.
+    pub av_offset_ms: i64,
+    /// Playback speed, 1.0 being normal; negative plays backwards. Beyond
+    /// `TRICK_MODE_RATE` either way only keyframes are shown and audio is
+    /// muted, e.g. to hold a button to fast-forward a cutscene.
+    pub rate: f64,
+    /// Keep voices at their pitch when `rate` is not 1.0. Takes effect
+    /// when the pipeline is created.
+    pub preserve_pitch: bool,
+    /// What happens at the end of the stream.
+    pub loop_mode: LoopMode,
+    /// In and out points to loop between instead of the whole stream, e.g.
+    /// the idle part of a longer FMV. Loops even with `LoopMode::Off`.
+    pub loop_segment: Option<(Duration, Duration)>,
+    /// Set while a `PingPong` loop plays in reverse.
+    pub playing_backwards: bool,
+    /// Equalizer and filters for the audio. Can be changed at runtime as
+    /// long as it was `Some` when the pipeline was created.
+    pub audio_effects: Option<AudioEffects>,
+    /// Which quality levels an HLS source may play, see
+    /// [`VideoPlayer::variants`].
+    pub variant: VariantSelection,
+    /// Pause while the primary window is unfocused or minimized and resume
+    /// once it is back, even if `VideoPluginConfig::pause_in_background` is
+    /// off.
+    pub pause_in_background: bool,
+    pub audio_backend: VideoAudioBackend,
+    /// Play the audio as a spatial source following this entity's
+    /// transform, for TVs and radios in a 3D world. Implies the `Bevy`
+    /// backend; needs a `SpatialListener` in the scene.
+    pub spatial_audio: bool,
+    /// Entity playing the `VideoAudioStream` with the `Bevy` backend.
+    pub audio_entity: Option<Entity>,
+    /// How many decoded frames may wait for presentation.
+    pub queue_policy: QueuePolicy,
+    /// Most bytes of decoded frames that may wait for presentation, on top
+    /// of `queue_policy`; see `GstPlayer::set_queue_byte_limit`.
+    pub max_queue_bytes: Option<usize>,
+    /// Buffer and queue sizes inside the pipeline. Takes effect when the
+    /// pipeline is created.
+    pub tuning: PipelineTuning,
+    /// Callback run on the decode thread for every frame before it becomes
+    /// a texture. Takes effect when the pipeline is created.
+    #[reflect(ignore)]
+    pub frame_processor: Option<FrameProcessor>,
+    /// Forward every bus message as a `VideoBusMessage` event.
+    pub raw_bus_messages: bool,
+    /// What to do while the node is hidden or clipped out of view.
+    pub when_hidden: HiddenPolicy,
+    /// Set while the node is hidden or clipped out of view.
+    pub hidden: bool,
+    /// Moments that send a `VideoCueReached` when the frame showing them is
+    /// presented.
+    pub cues: Vec<(Duration, CueId)>,
+    /// State and bookkeeping owned by the plugin.
+    pub runtime: PlayerRuntime,
+}
+
+impl Default for VideoPlayer {
+    fn default() -> Self {
+        VideoPlayer {
+            id: None,
+            width: 500.0,
+            height: 500.0,
+            fit: VideoFit::default(),
+            crop: None,
+            source: VideoSource::default(),
+            shared: false,
+            shared_with: None,
+            network: NetworkOptions::default(),
+            pipeline: None,
+            pause_on_buffering: false,
+            buffering: false,
+            reconnect: None,
+            reconnect_attempts: 0,
+            stall: None,
+            stalled: false,
+            thumbnail_scaling: None,
+            native_size: None,
+            downscaled: None,
+            dirty_regions: None,
+            current_frame: None,
+            poster: None,
+            poster_on_error: false,
+            on_end: EndFrame::default(),
+            autoplay: false,
+            preload: false,
+            has_alpha: false,
+            color_space: ColorSpace::default(),
+            hdr: HdrMode::default(),
+            transfer: VideoTransfer::default(),
+            presentation_anchor: None,
+            upload_time: Duration::ZERO,
+            av_offset_ms: 0,
+            rate: 1.0,
+            preserve_pitch: true,
+            loop_mode: LoopMode::default(),
+            loop_segment: None,
+            playing_backwards: false,
+            audio_effects: None,
+            variant: VariantSelection::default(),
+            pause_in_background: false,
+            audio_backend: VideoAudioBackend::default(),
+            spatial_audio: false,
+            audio_entity: None,
+            queue_policy: QueuePolicy::default(),
+            max_queue_bytes: None,
+            tuning: PipelineTuning::default(),
+            frame_processor: None,
+            raw_bus_messages: false,
+            when_hidden: HiddenPolicy::default(),
+            hidden: false,
+            cues: Vec::new(),
+            runtime: PlayerRuntime::default(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    NoFrame,
+    /// The frame's texture format has no image file equivalent.
+    UnsupportedFormat(TextureFormat),
+    Image(image::ImageError),
+}
+
+impl std::fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SnapshotError::NoFrame => write!(f, "no frame has been presented yet"),
+            SnapshotError::UnsupportedFormat(format) => {
+                write!(f, "cannot save a {format:?} frame")
+            }
+            SnapshotError::Image(err) => write!(f, "failed to save snapshot: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+impl VideoPlayer {
+    /// Changes through [`VideoPlayer::request`] and the plugin only.
+    pub fn state(&self) -> VideoState {
+        self.runtime.state
+    }
+
+    /// `loop_mode`, except that a `loop_segment` always loops.
+    pub fn effective_loop_mode(&self) -> LoopMode {
+        match (self.loop_mode, self.loop_segment) {
+            (LoopMode::Off, Some(_)) => LoopMode::Loop,
+            (mode, _) => mode,
+        }
+    }
+
+    /// Start and stop of the looped part; `None` stops at the end.
+    fn loop_bounds(&self) -> (Duration, Option<Duration>) {
+        match self.loop_segment {
+            Some((start, stop)) => (start, Some(stop)),
+            None => (Duration::ZERO, None),
+        }
+    }
+
+    /// Queues a state change. Every request made within a frame is applied
+    /// in order and reported with a `VideoStateChanged` event.
+    pub fn request(&mut self, command: VideoCommand) {
+        self.runtime.commands.push(command);
+    }
+
+    pub fn play(&mut self) {
+        self.request(VideoCommand::Play);
+    }
+
+    pub fn pause(&mut self) {
+        self.request(VideoCommand::Pause);
+    }
+
+    pub fn stop(&mut self) {
+        self.request(VideoCommand::Stop);
+    }
+
+    /// Seeks to the keyframe nearest `position`, fast enough for a scrub
+    /// bar.
+    pub fn seek(&mut self, position: Duration) {
+        self.seek_precise(position, SeekPrecision::Keyframe);
+    }
+
+    pub fn seek_precise(&mut self, position: Duration, precision: SeekPrecision) {
+        self.request(VideoCommand::Seek(position, precision));
+    }
+
+    pub fn uses_bevy_audio(&self) -> bool {
+        self.audio_backend == VideoAudioBackend::Bevy || self.spatial_audio
+    }
+
+    /// Observer handle to this player's pipeline, once it has been created.
+    pub fn share(&self) -> Option<SharedVideoSource> {
+        self.pipeline.as_ref().map(|pipeline| pipeline.share())
+    }
+
+    /// See [`GstPlayer::subscribe_frames`]. `None` until the pipeline
+    /// exists; subscribe again after it is rebuilt, e.g. on reconnect.
+    pub fn subscribe_frames(&self, capacity: usize) -> Option<Receiver<Arc<VideoInfo>>> {
+        self.pipeline
+            .as_ref()
+            .map(|pipeline| pipeline.subscribe_frames(capacity))
+    }
+
+    /// Chapters of the stream, empty until the container's table of
+    /// contents has been read.
+    pub fn chapters(&self) -> Vec<VideoChapter> {
+        self.pipeline
+            .as_ref()
+            .map(|pipeline| pipeline.chapters())
+            .unwrap_or_default()
+    }
+
+    /// Queues a seek to the start of chapter `index`, e.g. from a DVD-style
+    /// menu. Returns false if there is no such chapter.
+    pub fn seek_to_chapter(&mut self, index: usize) -> bool {
+        match self.chapters().get(index) {
+            Some(chapter) => {
+                self.seek(chapter.start);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// PTS of the frame currently on screen.
+    pub fn presented_pts(&self) -> Option<Duration> {
+        self.presentation_anchor.map(|(pts, _)| pts)
+    }
+
+    /// `Time::elapsed` at which the frame with `pts` was or will be
+    /// presented, extrapolated from the last presented frame at normal
+    /// speed. Predictions for future frames only hold while playing.
+    pub fn pts_to_time(&self, pts: Duration) -> Option<Duration> {
+        let (anchor_pts, anchor_time) = self.presentation_anchor?;
+        if pts >= anchor_pts {
+            Some(anchor_time + (pts - anchor_pts))
+        } else {
+            anchor_time.checked_sub(anchor_pts - pts)
+        }
+    }
+
+    /// Inverse of [`VideoPlayer::pts_to_time`]: the PTS on screen at `time`.
+    pub fn time_to_pts(&self, time: Duration) -> Option<Duration> {
+        let (anchor_pts, anchor_time) = self.presentation_anchor?;
+        if time >= anchor_time {
+            Some(anchor_pts + (time - anchor_time))
+        } else {
+            anchor_pts.checked_sub(anchor_time - time)
+        }
+    }
+
+    /// Quality levels of an HLS source, for `variant`. Empty until the
+    /// master playlist has been downloaded, and for other sources.
+    pub fn variants(&self) -> Vec<HlsVariant> {
+        self.pipeline
+            .as_ref()
+            .map(|pipeline| pipeline.variants())
+            .unwrap_or_default()
+    }
+
+    /// Download counters of the source, `None` until the pipeline exists.
+    pub fn network_stats(&self) -> Option<NetworkStats> {
+        self.pipeline
+            .as_ref()
+            .map(|pipeline| pipeline.network_stats())
+    }
+
+    /// Whether the source has no video stream, so only `poster` is shown.
+    pub fn is_audio_only(&self) -> bool {
+        self.pipeline
+            .as_ref()
+            .is_some_and(|pipeline| pipeline.is_audio_only())
+    }
+
+    /// Texture to show for the player: the current frame, or the poster
+    /// before there is one.
+    pub fn displayed_texture(&self) -> Option<Handle<Image>> {
+        self.current_frame.clone().or_else(|| self.poster.clone())
+    }
+
+    /// Copy of the frame currently on screen.
+    pub fn snapshot(&self, images: &Assets<Image>) -> Option<Image> {
+        if let Some(frame) = self
+            .dirty_regions
+            .as_ref()
+            .and_then(|dirty| dirty.frame.as_ref())
+        {
+            return Some(frame.clone());
+        }
+        self.current_frame
+            .as_ref()
+            .and_then(|handle| images.get(handle))
+            .cloned()
+    }
+
+    /// Writes the frame currently on screen to `path`; the format follows
+    /// the file extension. `HdrMode::Passthrough` frames keep their 16 bits
+    /// per channel, so they need a format that supports it, like PNG.
+    pub fn save_snapshot(
+        &self,
+        images: &Assets<Image>,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<(), SnapshotError> {
+        let frame = self.snapshot(images).ok_or(SnapshotError::NoFrame)?;
+        let (width, height) = (frame.width(), frame.height());
+        match frame.texture_descriptor.format {
+            TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => {
+                image::RgbaImage::from_raw(width, height, frame.data)
+                    .ok_or(SnapshotError::NoFrame)?
+                    .save(path)
+            }
+            TextureFormat::Rgba16Unorm => {
+                let data = frame
+                    .data
+                    .chunks_exact(2)
+                    .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+                    .collect();
+                image::ImageBuffer::<image::Rgba<u16>, Vec<u16>>::from_raw(width, height, data)
+                    .ok_or(SnapshotError::NoFrame)?
+                    .save(path)
+            }
+            format => return Err(SnapshotError::UnsupportedFormat(format)),
+        }
+        .map_err(SnapshotError::Image)
+    }
+}
+
+/// Decode at reduced size and frame rate while the UI node is much smaller
+/// than the video.
+#[derive(Debug, Clone, Copy, Reflect)]
+pub struct ThumbnailScaling {
+    /// Node size, as a fraction of the native size, under which the
+    /// player switches to thumbnail decoding.
+    pub threshold: f32,
+    pub framerate: i32,
+}
+
+impl Default for ThumbnailScaling {
+    fn default() -> Self {
+        ThumbnailScaling {
+            threshold: 0.25,
+            framerate: 10,
+        }
+    }
+}
+
+/// Where a player's audio goes. Without the `audio` feature players are
+/// silent and this is ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+pub enum VideoAudioBackend {
+    /// The default rodio output, shared by every player.
+    #[cfg_attr(not(target_os = "android"), default)]
+    Rodio,
+    /// A `bevy_audio` source, mixed with game sound and scaled by
+    /// `GlobalVolume`. Needs `AudioPlugin` added before `VideoPlugin`.
+    Bevy,
+    /// GStreamer's own audio sink for the platform, e.g. OpenSL ES on
+    /// Android, where it is the default. Ducking is not applied.
+    #[cfg_attr(target_os = "android", default)]
+    System,
+}
+
+/// How a frame is laid out inside the player's `width` x `height` rect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+pub enum VideoFit {
+    /// Scale to fit inside the rect, letterboxing or pillarboxing the rest.
+    #[default]
+    Contain,
+    /// Scale to fill the rect, overflowing on one axis. Give the parent node
+    /// `Overflow::clip()` to crop the overflow.
+    Cover,
+    /// Fill the rect exactly, ignoring the aspect ratio.
+    Stretch,
+    /// Show the frame at its native size, centered in the rect.
+    None,
+}
+
+impl VideoFit {
+    /// Size of the frame on screen for a `native` frame inside `rect`.
+    pub fn fitted_size(self, native: Vec2, rect: Vec2) -> Vec2 {
+        if native.x <= 0.0 || native.y <= 0.0 {
+            return rect;
+        }
+        let (sx, sy) = (rect.x / native.x, rect.y / native.y);
+        match self {
+            VideoFit::Contain => native * sx.min(sy),
+            VideoFit::Cover => native * sx.max(sy),
+            VideoFit::Stretch => rect,
+            VideoFit::None => native,
+        }
+    }
+}
+
+/// How a player continues at the end of its stream. Looping plays
+/// segments, so the jump back does not flush the pipeline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+pub enum LoopMode {
+    /// Stop and send `VideoEnded`.
+    #[default]
+    Off,
+    /// Start over from the beginning.
+    Loop,
+    /// Play backwards to the start, then forwards again, so ambient loops
+    /// never visibly jump. Needs a demuxer and decoder that support reverse
+    /// playback; all-intra codecs work best.
+    PingPong,
+}
+
+/// What a player shows once it is stopped or its stream ended.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Reflect)]
+pub enum EndFrame {
+    /// Keep the last frame on screen.
+    #[default]
+    Hold,
+    /// Replace the frame with a solid color.
+    Clear(Color),
+    /// Show `VideoPlayer::poster`; without one the last frame stays.
+    Poster,
+}
+
+/// What a player does while its node is not visible, e.g. previews
+/// scrolled out of a menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+pub enum HiddenPolicy {
+    /// Keep decoding and uploading frames.
+    #[default]
+    Keep,
+    /// Keep decoding so playback stays in time, but drop frames instead of
+    /// uploading them.
+    SkipUploads,
+    /// Pause the pipeline and resume where it was once visible again.
+    Pause,
+}
+
+/// How a player rebuilds its pipeline after the source errors out.
+#[derive(Debug, Clone, Copy, Reflect)]
+pub struct ReconnectPolicy {
+    pub max_retries: u32,
+    pub delay: Duration,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        ReconnectPolicy {
+            max_retries: 5,
+            delay: Duration::from_secs(2),
+        }
+    }
+}
+
+/// When a playing player counts as stalled and what it does about it.
+#[derive(Debug, Clone, Copy, Reflect)]
+pub struct StallPolicy {
+    /// How long no frame may arrive, outside buffering, before the player
+    /// counts as stalled.
+    pub timeout: Duration,
+    /// Seek to the current position, which flushes the pipeline and
+    /// restarts a wedged decoder or network source.
+    pub recover: bool,
+}
+
+impl Default for StallPolicy {
+    fn default() -> Self {
+        StallPolicy {
+            timeout: Duration::from_secs(5),
+            recover: true,
+        }
+    }
+}
+
+/// Sent when a playing player has not produced a frame for its
+/// `StallPolicy::timeout`, e.g. after a network hiccup.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct VideoStalled {
+    pub entity: Entity,
+    /// Position of the frame on screen.
+    pub position: Option<Duration>,
+    /// Whether a recovery seek was queued.
+    pub recovering: bool,
+}
+
+/// Sent whenever the source reports buffering progress (0-100).
+#[derive(Event, Debug, Clone, Copy)]
+pub struct VideoBuffering {
+    pub entity: Entity,
+    pub percent: i32,
+}
+
+/// Sent for every state transition applied from the command queue.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct VideoStateChanged {
+    pub entity: Entity,
+    pub previous: VideoState,
+    pub current: VideoState,
+}
+
+/// Sent when a player's pipeline fails.
+#[derive(Event, Debug, Clone)]
+pub enum VideoError {
+    /// The pipeline reported an error on its bus.
+    Pipeline { entity: Entity, message: String },
+    /// A GStreamer plugin the source needs isn't installed, so it won't
+    /// play until it is. `name` is the element, or a description of the
+    /// decoder, that is missing.
+    MissingPlugin {
+        entity: Entity,
+        name: String,
+        install_hint: String,
+    },
+}
+
+impl VideoError {
+    pub fn entity(&self) -> Entity {
+        match self {
+            VideoError::Pipeline { entity, .. } | VideoError::MissingPlugin { entity, .. } => {
+                *entity
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for VideoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VideoError::Pipeline { message, .. } => write!(f, "{message}"),
+            VideoError::MissingPlugin {
+                name, install_hint, ..
+            } => write!(f, "missing GStreamer plugin {name}: {install_hint}"),
+        }
+    }
+}
+
+/// Identifies a cue on a player, see `VideoPlayer::cues`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub struct CueId(pub u32);
+
+/// Sent when playback crosses one of a player's `cues`.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct VideoCueReached {
+    pub entity: Entity,
+    pub cue: CueId,
+    pub time: Duration,
+}
+
+/// Sent when a player's stream reaches its end.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct VideoEnded {
+    pub entity: Entity,
+}
+
+/// Every bus message of players with `raw_bus_messages` set, for
+/// information the curated events do not cover (tags, QoS, element
+/// messages...).
+#[derive(Event, Debug, Clone)]
+pub struct VideoBusMessage {
+    pub entity: Entity,
+    pub message: gst::Message,
+}
+
+/// Sent when the stream's frame size changes mid-play, e.g. when an
+/// adaptive stream switches variants. The node keeps its size; only the
+/// texture is replaced.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct VideoResolutionChanged {
+    pub entity: Entity,
+    pub previous: UVec2,
+    pub current: UVec2,
+}
+
+/// Sent for playing players every `VideoPluginConfig::progress_interval`,
+/// so UI and analytics don't have to query the pipeline every frame.
+/// `duration` is `None` for live streams and until it is known.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct VideoProgress {
+    pub entity: Entity,
+    pub position: Duration,
+    pub duration: Option<Duration>,
+}
+
+/// Sent when a player schedules a rebuild of its pipeline after an error.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct VideoReconnecting {
+    pub entity: Entity,
+    pub attempt: u32,
+}
+
+/// Settings shared by every player. Insert it before adding `VideoPlugin`
+/// to override the defaults, or change it at runtime.
+#[derive(Resource, Debug, Clone, Reflect)]
+#[reflect(Resource)]
+pub struct VideoPluginConfig {
+    /// Lip-sync correction for the display in milliseconds. Positive values
+    /// delay video relative to audio; use negative values for TVs that add
+    /// picture latency.
+    pub av_offset_ms: i64,
+    /// How long to wait for pipeline setup tasks on `AppExit` before
+    /// detaching them.
+    pub shutdown_timeout: Duration,
+    /// Pause every player while the primary window is unfocused or
+    /// minimized, see `VideoPlayer::pause_in_background`.
+    pub pause_in_background: bool,
+    /// Options for initializing GStreamer, applied when `VideoPlugin` is
+    /// added.
+    pub gstreamer: GstInitOptions,
+    /// How often playing players send `VideoProgress`; `None` disables it.
+    pub progress_interval: Option<Duration>,
+}
+
+impl Default for VideoPluginConfig {
+    fn default() -> Self {
+        VideoPluginConfig {
+            av_offset_ms: 0,
+            shutdown_timeout: Duration::from_secs(2),
+            pause_in_background: false,
+            gstreamer: GstInitOptions::default(),
+            progress_interval: Some(Duration::from_millis(250)),
+        }
+    }
 }
 
 pub struct VideoPlugin;
 
 impl Plugin for VideoPlugin {
-    fn build(&self, _: &mut App) {}
+    fn build(&self, app: &mut App) {
+        let options = app
+            .world
+            .get_resource::<VideoPluginConfig>()
+            .map(|config| config.gstreamer.clone())
+            .unwrap_or_default();
+        if let Err(err) = init_gstreamer(&options) {
+            error!("Failed to initialize GStreamer: {}", err);
+        }
+        build_dirty_regions(app);
+        build_video_projectors(app);
+        #[cfg(feature = "audio")]
+        if app.is_plugin_added::<AudioPlugin>() {
+            app.add_audio_source::<VideoAudioStream>().add_systems(
+                Update,
+                (attach_video_audio, follow_video_audio)
+                    .chain()
+                    .after(render_video_frame),
+            );
+        }
+        app.init_resource::<VideoPluginConfig>()
+            .register_type::<VideoPlayer>()
+            .register_type::<VideoPluginConfig>()
+            .register_type::<VideoPlayerConfig>()
+            .init_resource::<VideoWorkers>()
+            .init_resource::<VideoUsage>()
+            .add_event::<VideoBuffering>()
+            .add_event::<VideoStateChanged>()
+            .add_event::<VideoError>()
+            .add_event::<VideoReconnecting>()
+            .add_event::<VideoBusMessage>()
+            .add_event::<VideoEnded>()
+            .add_event::<VideoCueReached>()
+            .add_event::<VideoTranscodeProgress>()
+            .add_event::<VideoTranscodeFinished>()
+            .add_event::<VideoResolutionChanged>()
+            .add_event::<VideoProgress>()
+            .add_event::<VideoStalled>()
+            .add_event::<VideoPlaylistAdvanced>()
+            .add_event::<VideoWorkerPanicked>()
+            .add_event::<ControlVideo>()
+            .add_systems(
+                PreUpdate,
+                (queue_video_command_events, apply_video_commands).chain(),
+            )
+            .add_systems(Last, shutdown_video_workers_on_exit)
+            .add_systems(
+                Update,
+                run_av_calibration.run_if(resource_exists::<AvCalibration>),
+            )
+            .add_systems(
+                Update,
+                video_keyboard_shortcuts.run_if(resource_exists::<VideoShortcuts>),
+            )
+            .add_systems(
+                Update,
+                run_video_screensaver.run_if(resource_exists::<VideoScreensaver>),
+            )
+            .add_systems(Update, share_video_pipelines.before(render_video_frame))
+            .add_systems(Update, send_video_cues.after(render_video_frame))
+            .add_systems(Update, send_video_progress.after(render_video_frame))
+            .add_systems(Update, detect_video_stalls.after(render_video_frame))
+            .add_systems(Update, autoplay_video_players.before(render_video_frame))
+            .add_systems(Update, show_video_posters.after(render_video_frame))
+            .add_systems(
+                Update,
+                show_video_end_frames
+                    .after(render_video_frame)
+                    .before(show_video_posters),
+            )
+            .add_systems(Update, run_video_transcodes)
+            .add_systems(Update, sync_video_mesh_materials.after(render_video_frame))
+            .add_systems(Update, update_video_skyboxes)
+            .add_systems(Update, update_video_projectors.after(render_video_frame))
+            .add_systems(Update, update_video_textures.after(render_video_frame))
+            .add_systems(Update, pick_video_surfaces)
+            .add_systems(
+                PreUpdate,
+                load_video_player_configs.before(queue_video_command_events),
+            )
+            .add_systems(Update, sync_video_player_configs)
+            .add_systems(
+                Update,
+                advance_video_playlists
+                    .after(forward_player_messages)
+                    .before(render_video_frame),
+            )
+            .add_systems(
+                PostUpdate,
+                capture_video_recording.run_if(resource_exists::<VideoRecording>),
+            )
+            .add_systems(
+                Update,
+                schedule_video_decoding
+                    .after(throttle_hidden_players)
+                    .run_if(resource_exists::<VideoDecodePool>),
+            )
+            .add_systems(
+                Update,
+                (
+                    forward_player_messages,
+                    reconnect_video_players,
+                    shutdown_removed_players.before(reap_video_workers),
+                    reap_video_workers,
+                    update_video_usage,
+                    scale_thumbnail_players,
+                    apply_av_offset,
+                    apply_playback_rate,
+                    apply_audio_effects,
+                    apply_variant_selection,
+                    apply_loop_mode,
+                    pause_in_background,
+                    throttle_hidden_players,
+                    apply_video_fit,
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    build_video_controls,
+                    handle_video_controls,
+                    update_video_controls,
+                    load_scrub_previews,
+                    update_scrub_previews,
+                ),
+            );
+    }
+}
+
+fn handle_buffering(video_player: &mut VideoPlayer, percent: i32) {
+    if !video_player.pause_on_buffering || !matches!(video_player.state(), VideoState::Playing) {
+        return;
+    }
+    if let Some(pipeline) = video_player.pipeline.as_ref() {
+        if percent < 100 && !video_player.buffering {
+            pipeline.pause();
+            video_player.buffering = true;
+        } else if percent >= 100 && video_player.buffering {
+            pipeline.play();
+            video_player.buffering = false;
+        }
+    }
+}
+
+fn apply_video_command(video_player: &mut VideoPlayer, command: VideoCommand) -> VideoState {
+    let Some(pipeline) = video_player.pipeline.as_ref() else {
+        return video_player.state();
+    };
+    match command {
+        VideoCommand::Play => {
+            pipeline.play();
+            VideoState::Playing
+        }
+        VideoCommand::Pause => {
+            pipeline.pause();
+            VideoState::Paused
+        }
+        VideoCommand::Stop => {
+            pipeline.destroy();
+            VideoState::Stop
+        }
+        VideoCommand::Seek(position, precision) => {
+            pipeline.seek_precise(position, precision);
+            // The seek left segment mode; loops restart from here.
+            video_player.runtime.applied_loop_mode = None;
+            video_player.state()
+        }
+    }
+}
+
+/// Queues the command events sent since last frame on their players, in
+/// the order they were sent.
+pub fn queue_video_command_events(
+    mut events: EventReader<ControlVideo>,
+    mut query: Query<&mut VideoPlayer>,
+) {
+    for &ControlVideo { entity, command } in events.read() {
+        match query.get_mut(entity) {
+            Ok(mut video_player) => video_player.request(command),
+            Err(_) => warn!("{command:?} sent to {entity:?}, which has no VideoPlayer"),
+        }
+    }
+}
+
+/// Drains each player's command queue. Players whose pipeline does not exist
+/// yet keep their queue until it does.
+pub fn apply_video_commands(
+    mut query: Query<(Entity, &mut VideoPlayer)>,
+    mut state_events: EventWriter<VideoStateChanged>,
+) {
+    for (entity, mut video_player) in query.iter_mut() {
+        if video_player.runtime.commands.is_empty() || video_player.pipeline.is_none() {
+            continue;
+        }
+        let commands = std::mem::take(&mut video_player.runtime.commands);
+        for command in commands {
+            let previous = video_player.state();
+            let current = apply_video_command(&mut video_player, command);
+            if current != previous {
+                video_player.runtime.state = current;
+                state_events.send(VideoStateChanged {
+                    entity,
+                    previous,
+                    current,
+                });
+            }
+        }
+    }
+}
+
+/// Pauses opted-in players when the primary window loses focus or is
+/// minimized, and resumes the ones it paused when it comes back.
+pub fn pause_in_background(
+    mut focus_events: EventReader<WindowFocused>,
+    mut occluded_events: EventReader<WindowOccluded>,
+    primary: Query<Entity, With<PrimaryWindow>>,
+    config: Res<VideoPluginConfig>,
+    mut query: Query<&mut VideoPlayer>,
+    mut window_state: Local<(bool, bool)>,
+) {
+    let Ok(primary) = primary.get_single() else {
+        return;
+    };
+    let (unfocused, occluded) = &mut *window_state;
+    let was_background = *unfocused || *occluded;
+    for event in focus_events.read().filter(|event| event.window == primary) {
+        *unfocused = !event.focused;
+    }
+    for event in occluded_events
+        .read()
+        .filter(|event| event.window == primary)
+    {
+        *occluded = event.occluded;
+    }
+    let background = *unfocused || *occluded;
+    if background == was_background {
+        return;
+    }
+    for mut video_player in query.iter_mut() {
+        if background {
+            let opted_in = config.pause_in_background || video_player.pause_in_background;
+            if opted_in && video_player.state() == VideoState::Playing {
+                video_player.pause();
+                video_player.runtime.paused_in_background = true;
+            }
+        } else if video_player.runtime.paused_in_background {
+            video_player.play();
+            video_player.runtime.paused_in_background = false;
+        }
+    }
+}
+
+/// Tracks whether each player's node is visible, from `ViewVisibility` and
+/// the clip rect of scrolling parents, and applies its `when_hidden`.
+/// Visibility is computed in `PostUpdate`, so this lags by a frame.
+pub fn throttle_hidden_players(
+    mut query: Query<(
+        &mut VideoPlayer,
+        &ViewVisibility,
+        &Node,
+        &GlobalTransform,
+        Option<&CalculatedClip>,
+    )>,
+) {
+    for (mut video_player, view_visibility, node, transform, clip) in query.iter_mut() {
+        let clipped = clip.is_some_and(|clip| {
+            let rect = node.logical_rect(transform);
+            rect.intersect(clip.clip).is_empty()
+        });
+        let hidden = !view_visibility.get() || clipped;
+        if hidden == video_player.hidden {
+            continue;
+        }
+        video_player.hidden = hidden;
+        if hidden {
+            let pause = video_player.when_hidden == HiddenPolicy::Pause;
+            if pause && video_player.state() == VideoState::Playing {
+                video_player.pause();
+                video_player.runtime.paused_while_hidden = true;
+            }
+        } else if video_player.runtime.paused_while_hidden {
+            video_player.play();
+            video_player.runtime.paused_while_hidden = false;
+        }
+    }
+}
+
+/// Pushes changed playback rates to the pipelines. With the `Bevy` audio
+/// backend and no pitch preservation the audio sink is sped up instead of
+/// the rodio output.
+pub fn apply_playback_rate(
+    mut query: Query<&mut VideoPlayer>,
+    #[cfg(feature = "audio")] sinks: Query<&AudioSink>,
+    #[cfg(feature = "audio")] spatial_sinks: Query<&SpatialAudioSink>,
+) {
+    for mut video_player in query.iter_mut() {
+        let rate = video_player.rate;
+        if video_player.runtime.applied_rate == Some(rate) {
+            continue;
+        }
+        // New pipelines already run at normal speed.
+        if video_player.runtime.applied_rate.is_none() && rate == 1.0 {
+            video_player.runtime.applied_rate = Some(rate);
+            continue;
+        }
+        // Rate seeks need a prerolled pipeline.
+        let Some(pipeline) = video_player
+            .pipeline
+            .as_ref()
+            .filter(|_| video_player.presentation_anchor.is_some())
+        else {
+            continue;
+        };
+        pipeline.set_rate(rate);
+        video_player.runtime.applied_loop_mode = None;
+        #[cfg(feature = "audio")]
+        if !video_player.preserve_pitch && rate > 0.0 && rate <= crate::video::TRICK_MODE_RATE {
+            if let Some(audio) = video_player.audio_entity {
+                if let Ok(sink) = sinks.get(audio) {
+                    sink.set_speed(rate as f32);
+                }
+                if let Ok(sink) = spatial_sinks.get(audio) {
+                    sink.set_speed(rate as f32);
+                }
+            }
+        }
+        video_player.runtime.applied_rate = Some(rate);
+    }
+}
+
+/// Switches pipelines in or out of segment playback when `loop_mode`
+/// changes, or after a seek or rate change left it.
+pub fn apply_loop_mode(mut query: Query<&mut VideoPlayer>) {
+    for mut video_player in query.iter_mut() {
+        let mode = video_player.effective_loop_mode();
+        let segment = video_player.loop_segment;
+        if (video_player.runtime.applied_loop_mode == Some(mode)
+            && video_player.runtime.applied_loop_segment == segment)
+            || video_player.state() != VideoState::Playing
+        {
+            continue;
+        }
+        // Segment seeks need a prerolled pipeline.
+        let Some(pipeline) = video_player
+            .pipeline
+            .as_ref()
+            .filter(|_| video_player.presentation_anchor.is_some())
+        else {
+            continue;
+        };
+        let Some(position) = pipeline.position() else {
+            continue;
+        };
+        match mode {
+            // A non-segment seek ends the stream normally again.
+            LoopMode::Off if video_player.runtime.applied_loop_mode.is_some() => {
+                pipeline.seek(position)
+            }
+            LoopMode::Off => {}
+            LoopMode::Loop | LoopMode::PingPong => {
+                let (start, stop) = video_player.loop_bounds();
+                // Jump into the looped part when outside it.
+                let inside = position >= start && stop.is_none_or(|stop| position < stop);
+                let from = if inside { position } else { start };
+                pipeline.play_segment(from, stop, pipeline.rate(), true);
+            }
+        }
+        video_player.playing_backwards = false;
+        video_player.runtime.applied_loop_mode = Some(mode);
+        video_player.runtime.applied_loop_segment = segment;
+    }
+}
+
+/// Queues the next segment of a looping player. Returns false if the player
+/// no longer loops, so the segment end is the end of the stream.
+fn continue_video_loop(video_player: &mut VideoPlayer) -> bool {
+    let Some(pipeline) = video_player.pipeline.as_ref() else {
+        return false;
+    };
+    let rate = pipeline.rate();
+    let (start, stop) = video_player.loop_bounds();
+    match video_player.effective_loop_mode() {
+        LoopMode::Off => return false,
+        LoopMode::Loop => pipeline.play_segment(start, stop, rate, false),
+        LoopMode::PingPong => {
+            let backwards = !video_player.playing_backwards;
+            let rate = if backwards { -rate } else { rate };
+            pipeline.play_segment(start, stop, rate, false);
+            video_player.playing_backwards = backwards;
+        }
+    }
+    true
+}
+
+/// Sends the cues between the previously and currently presented frame.
+/// Seeking or looping back starts over from the new position, without
+/// sending the cues skipped over.
+pub fn send_video_cues(
+    mut query: Query<(Entity, &mut VideoPlayer)>,
+    mut cue_events: EventWriter<VideoCueReached>,
+) {
+    for (entity, mut video_player) in query.iter_mut() {
+        if video_player.cues.is_empty() {
+            continue;
+        }
+        let Some(current) = video_player.presented_pts() else {
+            continue;
+        };
+        let previous = video_player.runtime.cue_position;
+        if previous == Some(current) {
+            continue;
+        }
+        video_player.runtime.cue_position = Some(current);
+        let crossed = |time: Duration| match previous {
+            Some(previous) if previous < current => time > previous && time <= current,
+            Some(_) => false,
+            None => time <= current,
+        };
+        for &(time, cue) in video_player.cues.iter().filter(|(time, _)| crossed(*time)) {
+            cue_events.send(VideoCueReached { entity, cue, time });
+        }
+    }
+}
+
+/// Sends `VideoProgress` for playing players once their interval has
+/// passed since the last one.
+pub fn send_video_progress(
+    query: Query<(Entity, &VideoPlayer)>,
+    config: Res<VideoPluginConfig>,
+    time: Res<Time>,
+    mut progress_events: EventWriter<VideoProgress>,
+    mut last_sent: Local<HashMap<Entity, Duration>>,
+) {
+    let Some(interval) = config.progress_interval else {
+        return;
+    };
+    let now = time.elapsed();
+    for (entity, video_player) in query.iter() {
+        if video_player.state() != VideoState::Playing {
+            continue;
+        }
+        let due = last_sent
+            .get(&entity)
+            .is_none_or(|sent| now.saturating_sub(*sent) >= interval);
+        let Some(pipeline) = video_player.pipeline.as_ref().filter(|_| due) else {
+            continue;
+        };
+        let Some(position) = pipeline.position() else {
+            continue;
+        };
+        progress_events.send(VideoProgress {
+            entity,
+            position,
+            duration: pipeline.duration(),
+        });
+        last_sent.insert(entity, now);
+    }
+    last_sent.retain(|entity, _| query.contains(*entity));
+}
+
+/// Sends `VideoStalled` when a playing player's decoded frame count hasn't
+/// changed for its stall timeout, and queues a recovery seek if asked to.
+/// A recovered player is watched again from the seek on.
+pub fn detect_video_stalls(
+    mut query: Query<(Entity, &mut VideoPlayer)>,
+    time: Res<Time>,
+    mut stall_events: EventWriter<VideoStalled>,
+) {
+    let now = time.elapsed();
+    for (entity, mut video_player) in query.iter_mut() {
+        let Some(policy) = video_player.stall else {
+            continue;
+        };
+        // Audio-only sources never produce frames.
+        if video_player.state() != VideoState::Playing
+            || video_player.buffering
+            || video_player.is_audio_only()
+        {
+            video_player.runtime.stall_watch = None;
+            video_player.stalled = false;
+            continue;
+        }
+        let Some(pipeline) = video_player.pipeline.as_ref() else {
+            continue;
+        };
+        let samples = pipeline
+            .stats
+            .lock()
+            .map(|stats| stats.video_samples)
+            .unwrap_or_default();
+        let since = match video_player.runtime.stall_watch {
+            Some((seen, since)) if seen == samples => since,
+            _ => {
+                video_player.runtime.stall_watch = Some((samples, now));
+                video_player.stalled = false;
+                continue;
+            }
+        };
+        if video_player.stalled || now.saturating_sub(since) < policy.timeout {
+            continue;
+        }
+        let position = video_player.presented_pts();
+        let recovering = policy.recover && position.is_some();
+        warn!("Video player {entity:?} stalled at {position:?}");
+        stall_events.send(VideoStalled {
+            entity,
+            position,
+            recovering,
+        });
+        match position.filter(|_| recovering) {
+            Some(position) => {
+                video_player.seek_precise(position, SeekPrecision::Accurate);
+                video_player.runtime.stall_watch = Some((samples, now));
+            }
+            None => video_player.stalled = true,
+        }
+    }
+}
+
+/// Pushes changed audio effects to the pipelines.
+pub fn apply_audio_effects(mut query: Query<&mut VideoPlayer>) {
+    for mut video_player in query.iter_mut() {
+        let effects = video_player.audio_effects;
+        if effects.is_none() || video_player.runtime.applied_audio_effects == effects {
+            continue;
+        }
+        if let (Some(pipeline), Some(effects)) = (video_player.pipeline.as_ref(), effects) {
+            pipeline.set_audio_effects(&effects);
+            video_player.runtime.applied_audio_effects = Some(effects);
+        }
+    }
+}
+
+/// Pushes changed HLS variant selections to the pipelines.
+pub fn apply_variant_selection(mut query: Query<&mut VideoPlayer>) {
+    for mut video_player in query.iter_mut() {
+        let selection = video_player.variant;
+        if video_player.runtime.applied_variant == Some(selection) {
+            continue;
+        }
+        if let Some(pipeline) = video_player.pipeline.as_ref() {
+            pipeline.set_variant_selection(selection);
+            video_player.runtime.applied_variant = Some(selection);
+        }
+    }
+}
+
+/// Spawns the `bevy_audio` source for players using the `Bevy` backend once
+/// their pipeline exists.
+#[cfg(feature = "audio")]
+pub fn attach_video_audio(
+    mut commands: Commands,
+    mut query: Query<(&mut VideoPlayer, Option<&GlobalTransform>)>,
+    mut streams: ResMut<Assets<VideoAudioStream>>,
+) {
+    for (mut video_player, transform) in query.iter_mut() {
+        if !video_player.uses_bevy_audio() || video_player.audio_entity.is_some() {
+            continue;
+        }
+        let Some(stream) = video_player
+            .pipeline
+            .as_ref()
+            .and_then(|pipeline| pipeline.audio_stream())
+        else {
+            continue;
+        };
+        let source = streams.add(stream.clone());
+        let settings = PlaybackSettings::DESPAWN.with_spatial(video_player.spatial_audio);
+        let transform = transform.map(|t| t.compute_transform()).unwrap_or_default();
+        let audio = commands
+            .spawn((
+                AudioSourceBundle { source, settings },
+                TransformBundle::from_transform(transform),
+            ))
+            .id();
+        video_player.audio_entity = Some(audio);
+    }
+}
+
+/// Moves spatial audio sources along with their player.
+#[cfg(feature = "audio")]
+pub fn follow_video_audio(
+    players: Query<(&VideoPlayer, &GlobalTransform), Changed<GlobalTransform>>,
+    mut sources: Query<&mut Transform>,
+) {
+    for (video_player, global) in players.iter() {
+        let Some(audio) = video_player
+            .audio_entity
+            .filter(|_| video_player.spatial_audio)
+        else {
+            continue;
+        };
+        if let Ok(mut transform) = sources.get_mut(audio) {
+            *transform = global.compute_transform();
+        }
+    }
+}
+
+/// Pushes the combined global and per-player AV offset to pipelines whose
+/// offset changed.
+pub fn apply_av_offset(mut query: Query<&mut VideoPlayer>, config: Res<VideoPluginConfig>) {
+    for mut video_player in query.iter_mut() {
+        let offset = (config.av_offset_ms + video_player.av_offset_ms) * 1_000_000;
+        if video_player.runtime.applied_av_offset == Some(offset) {
+            continue;
+        }
+        if let Some(pipeline) = video_player.pipeline.as_ref() {
+            pipeline.set_av_offset(offset);
+            video_player.runtime.applied_av_offset = Some(offset);
+        }
+    }
+}
+
+fn schedule_reconnect(video_player: &mut VideoPlayer) -> Option<u32> {
+    let policy = video_player.reconnect?;
+    if video_player.reconnect_attempts >= policy.max_retries {
+        return None;
+    }
+    video_player.reconnect_attempts += 1;
+    video_player.runtime.reconnect_timer = Some(Timer::new(policy.delay, TimerMode::Once));
+    Some(video_player.reconnect_attempts)
+}
+
+pub fn forward_player_messages(
+    mut query: Query<(Entity, &mut VideoPlayer)>,
+    mut buffering_events: EventWriter<VideoBuffering>,
+    mut error_events: EventWriter<VideoError>,
+    mut reconnect_events: EventWriter<VideoReconnecting>,
+    mut bus_events: EventWriter<VideoBusMessage>,
+    mut ended_events: EventWriter<VideoEnded>,
+) {
+    for (entity, mut video_player) in query.iter_mut() {
+        let messages: Vec<PlayerMessage> = match video_player.pipeline.as_ref() {
+            Some(pipeline) => match pipeline.messages.lock() {
+                Ok(mut messages) => messages.drain(..).collect(),
+                Err(_) => continue,
+            },
+            None => continue,
+        };
+        for message in messages {
+            match message {
+                PlayerMessage::Buffering(percent) => {
+                    buffering_events.send(VideoBuffering { entity, percent });
+                    handle_buffering(&mut video_player, percent);
+                }
+                PlayerMessage::Error(message) => {
+                    error_events.send(VideoError::Pipeline { entity, message });
+                    if video_player.poster_on_error {
+                        video_player.current_frame = None;
+                    }
+                    if let Some(attempt) = schedule_reconnect(&mut video_player) {
+                        reconnect_events.send(VideoReconnecting { entity, attempt });
+                    }
+                }
+                // Retrying can't help until the plugin is installed.
+                PlayerMessage::MissingPlugin { name, install_hint } => {
+                    error!("Missing GStreamer plugin {}: {}", name, install_hint);
+                    if video_player.poster_on_error {
+                        video_player.current_frame = None;
+                    }
+                    error_events.send(VideoError::MissingPlugin {
+                        entity,
+                        name,
+                        install_hint,
+                    });
+                }
+                PlayerMessage::Eos => {
+                    ended_events.send(VideoEnded { entity });
+                }
+                PlayerMessage::SegmentDone => {
+                    if !continue_video_loop(&mut video_player) {
+                        ended_events.send(VideoEnded { entity });
+                    }
+                }
+                PlayerMessage::Bus(message) => {
+                    bus_events.send(VideoBusMessage { entity, message });
+                }
+            }
+        }
+    }
+}
+
+/// Rebuilds the pipeline of players whose reconnect delay elapsed, keeping
+/// the entity, its texture and the requested playback state.
+pub fn reconnect_video_players(
+    mut query: Query<(Entity, &mut VideoPlayer)>,
+    mut workers: ResMut<VideoWorkers>,
+    time: Res<Time>,
+) {
+    for (entity, mut video_player) in query.iter_mut() {
+        // Shutting down the failed pipeline frees its own slot.
+        let has_slot = workers.has_capacity() || video_player.pipeline.is_some();
+        let Some(timer) = video_player.runtime.reconnect_timer.as_mut() else {
+            continue;
+        };
+        if !timer.tick(time.delta()).finished() || !has_slot {
+            continue;
+        }
+        video_player.runtime.reconnect_timer = None;
+        if let Some(pipeline) = video_player.pipeline.take() {
+            pipeline.shutdown();
+        }
+        video_player.buffering = false;
+        initialize_video_player(entity, &mut video_player, &mut workers);
+        if video_player.state() == VideoState::Playing {
+            video_player.play();
+        }
+    }
+}
+
+fn thumbnail_size(physical_size: Vec2) -> UVec2 {
+    // Most converters want even dimensions.
+    let even = |v: f32| ((v.ceil() as u32).max(2) + 1) & !1;
+    UVec2::new(even(physical_size.x), even(physical_size.y))
+}
+
+/// Scale factor from UI logical pixels to physical pixels on the primary window.
+pub fn ui_scale_factor(window: Option<&Window>, ui_scale: &UiScale) -> f32 {
+    window.map(|window| window.scale_factor()).unwrap_or(1.0) * ui_scale.0
+}
+
+/// Switches players between native and thumbnail decoding depending on the
+/// physical size of the UI node showing them.
+pub fn scale_thumbnail_players(
+    mut query: Query<(&mut VideoPlayer, &Node)>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    ui_scale: Res<UiScale>,
+) {
+    let scale_factor = ui_scale_factor(windows.get_single().ok(), &ui_scale);
+    for (mut video_player, node) in query.iter_mut() {
+        let (Some(scaling), Some(native_size)) =
+            (video_player.thumbnail_scaling, video_player.native_size)
+        else {
+            continue;
+        };
+        let physical_size = node.size() * scale_factor;
+        let limit = native_size.as_vec2() * scaling.threshold;
+        let small = physical_size.x > 0.0
+            && physical_size.y > 0.0
+            && physical_size.x < limit.x
+            && physical_size.y < limit.y;
+        let target = small.then(|| thumbnail_size(physical_size));
+        let unchanged = match (target, video_player.downscaled) {
+            (None, None) => true,
+            // Ignore small size changes so animated nodes don't renegotiate every frame.
+            (Some(target), Some(current)) => {
+                let delta = (target.as_vec2() - current.as_vec2()).abs();
+                delta.x <= current.x as f32 * 0.25 && delta.y <= current.y as f32 * 0.25
+            }
+            _ => false,
+        };
+        if unchanged {
+            continue;
+        }
+        if let Some(pipeline) = video_player.pipeline.as_ref() {
+            match target {
+                Some(size) => {
+                    pipeline.set_video_scale(Some((size.x, size.y)), Some(scaling.framerate))
+                }
+                None => pipeline.set_video_scale(None, None),
+            }
+            video_player.downscaled = target;
+        }
+    }
+}
+
+/// Sizes each player's node according to its `fit`, centering it in the
+/// `width` x `height` rect with margins. Cover uses negative margins, so the
+/// node overflows the rect on one axis.
+pub fn apply_video_fit(mut query: Query<(&VideoPlayer, &mut Style)>) {
+    for (video_player, mut style) in query.iter_mut() {
+        let rect = Vec2::new(video_player.width, video_player.height);
+        let native = match video_player.crop {
+            Some(crop) => Some(crop.size()),
+            None => video_player.native_size.map(|native| native.as_vec2()),
+        };
+        let size = match native {
+            Some(native) => video_player.fit.fitted_size(native, rect),
+            None => rect,
+        };
+        let offset = (rect - size) / 2.0;
+        let width = Val::Px(size.x);
+        let height = Val::Px(size.y);
+        let margin = UiRect::axes(Val::Px(offset.x), Val::Px(offset.y));
+        if style.width != width || style.height != height || style.margin != margin {
+            style.width = width;
+            style.height = height;
+            style.margin = margin;
+        }
+    }
+}
+
+/// Wraps a decoded frame into a Bevy image, converting deep color frames
+/// according to `hdr`.
+pub fn frame_to_image(frame: VideoInfo, hdr: HdrMode) -> Option<Image> {
+    let _span = info_span!("video_frame_to_image", frame.width, frame.height).entered();
+    if frame.format == gst_video::VideoFormat::Rgba64Le {
+        return rgba64_to_image(frame.width, frame.height, frame.data, frame.transfer, hdr);
+    }
+    let rbg_data = image::RgbaImage::from_raw(frame.width, frame.height, frame.data)?;
+    Some(Image::from_dynamic(
+        DynamicImage::ImageRgba8(rbg_data),
+        true,
+        RenderAssetUsages::default(),
+    ))
 }
 
+/// Crops `frame` to `crop`, given in native pixels, rescaling it when the
+/// pipeline decodes at a reduced size.
+fn crop_frame(frame: &mut VideoInfo, crop: Rect, native_size: Option<UVec2>) {
+    let frame_size = Vec2::new(frame.width as f32, frame.height as f32);
+    let scale = match native_size {
+        Some(native) if native.x > 0 && native.y > 0 => frame_size / native.as_vec2(),
+        _ => Vec2::ONE,
+    };
+    let min = (crop.min * scale).max(Vec2::ZERO).floor();
+    let size = (crop.size() * scale).round();
+    frame.crop(min.x as u32, min.y as u32, size.x as u32, size.y as u32);
+}
+
+/// Puts `canvas` on screen, either as a new texture or, with dirty regions
+/// enabled, as partial uploads into the current one.
+fn present_frame(
+    dirty_regions: &mut Option<DirtyRegions>,
+    current_frame: &mut Option<Handle<Image>>,
+    image_handle: &mut UiImage,
+    images: &mut Assets<Image>,
+    uploads: &mut DirtyRegionUploads,
+    mut canvas: Image,
+) {
+    let _span = info_span!("video_present_frame").entered();
+    let current = current_frame.as_ref().map(|handle| handle.id());
+    if let Some(dirty) = dirty_regions.as_mut() {
+        let bands = dirty.update(canvas);
+        let frame = dirty.frame.as_ref().expect("update keeps the frame");
+        if let (Some(bands), Some(current)) = (bands, current) {
+            uploads.push(current, frame, &bands);
+            return;
+        }
+        // Only full uploads copy the frame, as it is both kept for the
+        // next comparison and handed to a new texture.
+        canvas = frame.clone();
+    }
+    image_handle.texture = images.add(canvas);
+    *current_frame = Some(image_handle.texture.clone());
+}
+
+/// Presents the newest decoded frame. The appsink syncs to the pipeline
+/// clock, AV offset included, so presentation follows the clock instead
+/// of drifting with Bevy's frame rate. Frames it skipped count as dropped.
 fn handle_playing_state(
     video_player: &mut VideoPlayer,
     image_handle: &mut UiImage,
     images: &mut Assets<Image>,
+    uploads: &mut DirtyRegionUploads,
     time: &Res<Time>,
+) -> Option<(UVec2, UVec2)> {
+    let pipeline = video_player.pipeline.as_ref()?;
+    let (frame, skipped) = pipeline.frame.pop_newest();
+    if skipped > 0 {
+        if let Ok(mut stats) = pipeline.stats.lock() {
+            stats.dropped_frames += skipped;
+        }
+    }
+    let mut data = frame?;
+    let (width, height, frame_pts) = (data.width, data.height, data.pts);
+    let mut resized = None;
+    // Thumbnail scaling changes the frame size on purpose.
+    if video_player.downscaled.is_none() {
+        let size = UVec2::new(width, height);
+        if let Some(previous) = video_player.native_size {
+            if previous != size {
+                resized = Some((previous, size));
+            }
+        }
+        video_player.native_size = Some(size);
+    }
+    if video_player.hidden && video_player.when_hidden == HiddenPolicy::SkipUploads {
+        return resized;
+    }
+    if let Some(crop) = video_player.crop {
+        crop_frame(&mut data, crop, video_player.native_size);
+    }
+    let (has_alpha, transfer) = (data.has_alpha, data.transfer);
+    let upload_start = Instant::now();
+    if let Some(canvas) = frame_to_image(data, video_player.hdr) {
+        present_frame(
+            &mut video_player.dirty_regions,
+            &mut video_player.current_frame,
+            image_handle,
+            images,
+            uploads,
+            canvas,
+        );
+        video_player.upload_time = upload_start.elapsed();
+        video_player.has_alpha = has_alpha;
+        video_player.transfer = transfer;
+        video_player.presentation_anchor = Some((Duration::from_nanos(frame_pts), time.elapsed()));
+        video_player.reconnect_attempts = 0;
+        video_player.runtime.end_frame_shown = false;
+    }
+    resized
+}
+
+/// Requests playback for `autoplay` players whose pipeline has prerolled.
+pub fn autoplay_video_players(mut query: Query<&mut VideoPlayer>) {
+    for mut video_player in query.iter_mut() {
+        if !video_player.autoplay
+            || video_player.state() != VideoState::Ready
+            || video_player.runtime.commands.contains(&VideoCommand::Play)
+        {
+            continue;
+        }
+        let prerolled = video_player
+            .pipeline
+            .as_ref()
+            .is_some_and(|pipeline| pipeline.is_prerolled());
+        if prerolled {
+            video_player.play();
+        }
+    }
+}
+
+/// Shows the poster of players without a frame on screen.
+pub fn show_video_posters(mut query: Query<(&VideoPlayer, &mut UiImage)>) {
+    for (video_player, mut image) in query.iter_mut() {
+        if video_player.current_frame.is_some() {
+            continue;
+        }
+        if let Some(poster) = video_player.poster.as_ref() {
+            if image.texture != *poster {
+                image.texture = poster.clone();
+            }
+        }
+    }
+}
+
+/// Applies `VideoPlayer::on_end` to stopped players, and to ended ones
+/// once their queued frames are shown. Playlists moving on to another item
+/// keep their frame.
+pub fn show_video_end_frames(
+    mut query: Query<(
+        Entity,
+        &mut VideoPlayer,
+        &mut UiImage,
+        Option<&VideoPlaylist>,
+    )>,
+    mut ended_events: EventReader<VideoEnded>,
+    mut ended: Local<HashSet<Entity>>,
+    mut images: ResMut<Assets<Image>>,
 ) {
-    if let Ok(mut player_time) = video_player.timer.lock() {
-        if player_time.tick(time.delta()).just_finished() {
-            if let Some(ref_pipeline) = video_player.pipeline.as_ref() {
-                if let Ok(mut frames) = ref_pipeline.frame.lock() {
-                    if let Some(data) = frames.pop_front() {
-                        if let Some(rbg_data) =
-                            image::RgbaImage::from_raw(data.width, data.height, data.data)
-                        {
-                            let canvas = Image::from_dynamic(
-                                DynamicImage::ImageRgba8(rbg_data),
-                                true,
-                                RenderAssetUsages::default(),
-                            );
-                            image_handle.texture = images.add(canvas);
-                            if let Ok(mut pts) = ref_pipeline.previous_pts.lock() {
-                                let dt = (data.pts - *pts) / 1_000_000;
-                                player_time.set_duration(Duration::from_millis(dt));
-                                *pts = data.pts;
-                            }
-                        }
-                    }
-                }
+    ended.extend(ended_events.read().map(|event| event.entity));
+    for (entity, mut video_player, mut image, playlist) in query.iter_mut() {
+        let has_next = playlist.is_some_and(|playlist| playlist.has_next());
+        let drained = video_player
+            .pipeline
+            .as_ref()
+            .map(|pipeline| pipeline.queued_frames().0 == 0)
+            .unwrap_or(true);
+        if ended.contains(&entity) && (drained || has_next) {
+            ended.remove(&entity);
+            if !has_next {
+                apply_end_frame(&mut video_player, &mut image, &mut images);
             }
+        } else if video_player.state() == VideoState::Stop {
+            apply_end_frame(&mut video_player, &mut image, &mut images);
         }
     }
+    ended.retain(|entity| query.contains(*entity));
 }
 
-fn initialize_video_player(video_player: &mut VideoPlayer) {
-    let pipeline = GstPlayer::new(video_player.uri.as_str());
-    let pipeline_clone = Arc::new(Mutex::new(pipeline.clone()));
-    thread::spawn(move || {
-        if let Ok(mut pipeline) = pipeline_clone.lock() {
-            pipeline.start();
+fn apply_end_frame(
+    video_player: &mut VideoPlayer,
+    image: &mut UiImage,
+    images: &mut Assets<Image>,
+) {
+    if video_player.runtime.end_frame_shown {
+        return;
+    }
+    video_player.runtime.end_frame_shown = true;
+    match video_player.on_end {
+        EndFrame::Hold => return,
+        EndFrame::Clear(color) => {
+            let fill = Image::new_fill(
+                Extent3d {
+                    width: 1,
+                    height: 1,
+                    depth_or_array_layers: 1,
+                },
+                TextureDimension::D2,
+                &color.as_rgba_u8(),
+                TextureFormat::Rgba8UnormSrgb,
+                RenderAssetUsages::default(),
+            );
+            image.texture = images.add(fill);
+            video_player.current_frame = Some(image.texture.clone());
         }
-    });
+        EndFrame::Poster if video_player.poster.is_some() => {
+            video_player.current_frame = None;
+        }
+        EndFrame::Poster => return,
+    }
+    // The next frame is uploaded whole rather than diffed against the
+    // one that was replaced.
+    if let Some(dirty) = video_player.dirty_regions.as_mut() {
+        dirty.frame = None;
+    }
+}
+
+/// Presents the prerolled frame of a player that hasn't started, once the
+/// pipeline has one.
+fn show_preroll_frame(
+    video_player: &mut VideoPlayer,
+    image_handle: &mut UiImage,
+    images: &mut Assets<Image>,
+    uploads: &mut DirtyRegionUploads,
+) {
+    let Some(mut data) = video_player
+        .pipeline
+        .as_ref()
+        .and_then(|pipeline| pipeline.preroll_frame())
+    else {
+        return;
+    };
+    if video_player.downscaled.is_none() {
+        video_player.native_size = Some(UVec2::new(data.width, data.height));
+    }
+    if let Some(crop) = video_player.crop {
+        crop_frame(&mut data, crop, video_player.native_size);
+    }
+    let (has_alpha, transfer) = (data.has_alpha, data.transfer);
+    if let Some(canvas) = frame_to_image(data, video_player.hdr) {
+        present_frame(
+            &mut video_player.dirty_regions,
+            &mut video_player.current_frame,
+            image_handle,
+            images,
+            uploads,
+            canvas,
+        );
+        video_player.has_alpha = has_alpha;
+        video_player.transfer = transfer;
+        video_player.runtime.preloaded = true;
+        video_player.runtime.end_frame_shown = false;
+    }
+}
+
+/// Creates a pipeline for `source` with the player's settings, without
+/// starting it.
+pub(crate) fn build_pipeline(video_player: &VideoPlayer, source: &VideoSource) -> GstPlayer {
+    let options = PlayerOptions {
+        preserve_pitch: video_player.preserve_pitch,
+        audio_effects: video_player.audio_effects.is_some(),
+        system_audio: video_player.audio_backend == VideoAudioBackend::System
+            && !video_player.uses_bevy_audio(),
+        tuning: video_player.tuning,
+    };
+    let mut pipeline = GstPlayer::from_source_with(source, &options);
+    if video_player.uses_bevy_audio() {
+        pipeline.use_bevy_audio();
+    }
+    if !video_player.network.is_empty() {
+        pipeline.set_network_options(&video_player.network);
+    }
+    pipeline.set_color_space(video_player.color_space);
+    pipeline.set_raw_messages(video_player.raw_bus_messages);
+    pipeline.set_frame_processor(video_player.frame_processor.clone());
+    pipeline.set_queue_policy(video_player.queue_policy);
+    pipeline.set_queue_byte_limit(video_player.max_queue_bytes);
+    pipeline
+}
+
+/// Makes `pipeline` the player's, so per-pipeline settings are applied to
+/// it again.
+pub(crate) fn install_pipeline(video_player: &mut VideoPlayer, pipeline: GstPlayer) {
     video_player.pipeline = Some(pipeline);
+    video_player.downscaled = None;
+    video_player.runtime.applied_av_offset = None;
+    video_player.runtime.applied_rate = None;
+    video_player.runtime.applied_loop_mode = None;
+    video_player.runtime.applied_audio_effects = None;
+    video_player.runtime.applied_variant = None;
+    video_player.runtime.preloaded = false;
+    video_player.runtime.stall_watch = None;
+    // The previous stream despawns its entity once closed.
+    video_player.audio_entity = None;
+}
+
+fn initialize_video_player(
+    entity: Entity,
+    video_player: &mut VideoPlayer,
+    workers: &mut VideoWorkers,
+) {
+    let pipeline = build_pipeline(video_player, &video_player.source);
+    workers.spawn(entity, pipeline.control_handle());
+    install_pipeline(video_player, pipeline);
 }
 
 pub fn render_video_frame(
-    mut query: Query<(&mut VideoPlayer, &mut UiImage)>,
+    mut query: Query<(Entity, &mut VideoPlayer, &mut UiImage)>,
     mut images: ResMut<Assets<Image>>,
+    mut workers: ResMut<VideoWorkers>,
+    mut uploads: ResMut<DirtyRegionUploads>,
+    mut resolution_events: EventWriter<VideoResolutionChanged>,
     time: Res<Time>,
 ) {
-    for (mut video_player, mut image_handle) in query.iter_mut() {
-        match video_player.state {
+    for (entity, mut video_player, mut image_handle) in query.iter_mut() {
+        match video_player.state() {
             VideoState::Playing => {
-                handle_playing_state(&mut video_player, &mut image_handle, &mut images, &time)
-            }
-            VideoState::Init => {
-                if video_player.id.is_some() {
-                    video_player.state = VideoState::Ready;
-                    initialize_video_player(&mut video_player);
-                }
-            }
-            VideoState::Start => {
-                video_player.state = VideoState::Playing;
-                if let Some(video_player) = video_player.pipeline.as_ref() {
-                    video_player.play();
+                let resized = handle_playing_state(
+                    &mut video_player,
+                    &mut image_handle,
+                    &mut images,
+                    &mut uploads,
+                    &time,
+                );
+                if let Some((previous, current)) = resized {
+                    resolution_events.send(VideoResolutionChanged {
+                        entity,
+                        previous,
+                        current,
+                    });
                 }
             }
-            VideoState::Paused => {
-                if let Some(video_player) = video_player.pipeline.as_ref() {
-                    video_player.pause();
-                }
+            VideoState::Ready if video_player.preload && !video_player.runtime.preloaded => {
+                show_preroll_frame(
+                    &mut video_player,
+                    &mut image_handle,
+                    &mut images,
+                    &mut uploads,
+                );
             }
-            VideoState::Stop => {
-                if let Some(video_player) = video_player.pipeline.as_ref() {
-                    video_player.destroy();
+            VideoState::Init if video_player.id.is_some() && !video_player.runtime.evicted => {
+                if workers.has_capacity() {
+                    video_player.runtime.state = VideoState::Ready;
+                    initialize_video_player(entity, &mut video_player, &mut workers);
+                } else {
+                    workers.warn_held_back(entity);
                 }
             }
             _ => {}
@@ -120,18 +2031,37 @@ pub fn render_video_frame(
     }
 }
 
-pub fn insert_video_component(
+pub fn insert_video_component(images: ResMut<Assets<Image>>, default_size: Vec2) -> ImageBundle {
+    insert_video_component_scaled(images, default_size, 1.0)
+}
+
+/// Like `insert_video_component`, but sizes the placeholder texture in
+/// physical pixels so it stays crisp on HiDPI displays. `default_size` is
+/// still the logical size of the node.
+pub fn insert_video_component_scaled(
     mut images: ResMut<Assets<Image>>,
     default_size: Vec2,
+    scale_factor: f32,
+) -> ImageBundle {
+    video_image_bundle(&mut images, default_size, scale_factor)
+}
+
+/// Node with a placeholder texture, shared by the `insert_video_component`
+/// helpers and systems that spawn players themselves.
+pub(crate) fn video_image_bundle(
+    images: &mut Assets<Image>,
+    default_size: Vec2,
+    scale_factor: f32,
 ) -> ImageBundle {
     let mut canvas = Image::from_dynamic(
         DynamicImage::new_rgb8(500, 500),
         true,
         RenderAssetUsages::default(),
     );
+    let physical_size = (default_size * scale_factor).round();
     canvas.resize(Extent3d {
-        width: default_size.x as u32,
-        height: default_size.y as u32,
+        width: physical_size.x as u32,
+        height: physical_size.y as u32,
         ..default()
     });
     let image_handle = images.add(canvas);
@@ -0,0 +1,120 @@
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+
+use bevy::prelude::*;
+
+use crate::{
+    plugin::{VideoPlayer, VideoState},
+    video::SeekPrecision,
+};
+
+/// Caps how many players decode at once, for galleries with more videos
+/// than the machine can play together. Insert it to enable. Players over
+/// the limit are paused, least recently visible first, and resume as slots
+/// free up; with `evict_after` their pipelines are torn down too and rebuilt
+/// at the same position.
+#[derive(Resource, Debug)]
+pub struct VideoDecodePool {
+    pub max_active: usize,
+    /// Drop the pipeline of players paused by the pool and hidden for this
+    /// long. `None` keeps them paused.
+    pub evict_after: Option<Duration>,
+    last_visible: HashMap<Entity, Duration>,
+    throttled: HashSet<Entity>,
+    /// The position of evicted players.
+    evicted: HashMap<Entity, Option<Duration>>,
+}
+
+impl VideoDecodePool {
+    pub fn new(max_active: usize) -> Self {
+        VideoDecodePool {
+            max_active,
+            evict_after: None,
+            last_visible: HashMap::new(),
+            throttled: HashSet::new(),
+            evicted: HashMap::new(),
+        }
+    }
+
+    pub fn with_eviction(mut self, evict_after: Duration) -> Self {
+        self.evict_after = Some(evict_after);
+        self
+    }
+
+    /// Whether the pool is holding `entity` back.
+    pub fn is_throttled(&self, entity: Entity) -> bool {
+        self.throttled.contains(&entity) || self.evicted.contains_key(&entity)
+    }
+
+    fn forget(&mut self, entity: Entity) {
+        self.last_visible.remove(&entity);
+        self.throttled.remove(&entity);
+        self.evicted.remove(&entity);
+    }
+}
+
+fn evict(video_player: &mut VideoPlayer) -> Option<Duration> {
+    let position = video_player.presented_pts();
+    if let Some(pipeline) = video_player.pipeline.take() {
+        pipeline.shutdown();
+    }
+    // Stays in `Init` until the pool restores it.
+    video_player.runtime.state = VideoState::Init;
+    video_player.runtime.evicted = true;
+    position
+}
+
+/// Lets the most recently visible players that want to play decode, up to
+/// `max_active`, and pauses or evicts the rest.
+pub fn schedule_video_decoding(
+    mut pool: ResMut<VideoDecodePool>,
+    mut query: Query<(Entity, &mut VideoPlayer)>,
+    mut removed: RemovedComponents<VideoPlayer>,
+    time: Res<Time>,
+) {
+    for entity in removed.read() {
+        pool.forget(entity);
+    }
+    let now = time.elapsed();
+    let mut wanting = Vec::new();
+    for (entity, video_player) in query.iter() {
+        if !video_player.hidden {
+            pool.last_visible.insert(entity, now);
+        }
+        if video_player.state() == VideoState::Playing || pool.is_throttled(entity) {
+            let last_visible = pool.last_visible.get(&entity).copied().unwrap_or_default();
+            wanting.push((entity, last_visible));
+        }
+    }
+    wanting.sort_by_key(|&(_, last_visible)| Reverse(last_visible));
+
+    for (rank, (entity, last_visible)) in wanting.into_iter().enumerate() {
+        let Ok((_, mut video_player)) = query.get_mut(entity) else {
+            continue;
+        };
+        if rank < pool.max_active {
+            if pool.throttled.remove(&entity) {
+                video_player.play();
+            } else if let Some(position) = pool.evicted.remove(&entity) {
+                video_player.runtime.evicted = false;
+                if let Some(position) = position {
+                    video_player.seek_precise(position, SeekPrecision::Accurate);
+                }
+                video_player.play();
+            }
+        } else if video_player.state() == VideoState::Playing && !pool.throttled.contains(&entity) {
+            video_player.pause();
+            pool.throttled.insert(entity);
+        } else if pool.throttled.contains(&entity)
+            && pool
+                .evict_after
+                .is_some_and(|after| now.saturating_sub(last_visible) >= after)
+        {
+            pool.throttled.remove(&entity);
+            pool.evicted.insert(entity, evict(&mut video_player));
+        }
+    }
+}
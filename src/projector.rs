@@ -0,0 +1,138 @@
+use bevy::{
+    asset::load_internal_asset,
+    pbr::{ExtendedMaterial, MaterialExtension, PbrPlugin},
+    prelude::*,
+    render::render_resource::{AsBindGroup, ShaderRef},
+};
+
+use crate::plugin::VideoPlayer;
+
+const PROJECTOR_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(0x5b1c_3f0e_9a47_4d2b_8e61_c07a_92d4_e3f1);
+
+/// Material for geometry lit by a `VideoProjector`: the `StandardMaterial`
+/// surface with the projected frame added on top as light.
+pub type VideoProjectorMaterial = ExtendedMaterial<StandardMaterial, VideoProjectorExtension>;
+
+/// Projector half of `VideoProjectorMaterial`; kept up to date by
+/// `update_video_projectors`.
+#[derive(Asset, AsBindGroup, Reflect, Debug, Clone)]
+pub struct VideoProjectorExtension {
+    /// World to projector clip space.
+    #[uniform(100)]
+    pub view_proj: Mat4,
+    #[uniform(100)]
+    pub intensity: f32,
+    #[texture(101)]
+    #[sampler(102)]
+    pub frame: Option<Handle<Image>>,
+}
+
+impl Default for VideoProjectorExtension {
+    fn default() -> Self {
+        VideoProjectorExtension {
+            view_proj: Mat4::ZERO,
+            intensity: 0.0,
+            frame: None,
+        }
+    }
+}
+
+impl MaterialExtension for VideoProjectorExtension {
+    fn fragment_shader() -> ShaderRef {
+        PROJECTOR_SHADER_HANDLE.into()
+    }
+}
+
+/// Projects the frames of `player` along this entity's -Z, like a cinema
+/// projector or a spotlight gobo. Only meshes with a
+/// `VideoProjectorMaterial` and a `VideoProjectorReceiver` pointing here
+/// show the image; there is no occlusion, so it also lands on the back of
+/// walls inside the frustum.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct VideoProjector {
+    pub player: Entity,
+    /// Vertical field of view, in radians.
+    pub fov: f32,
+    /// Width over height; `None` uses the video's.
+    pub aspect: Option<f32>,
+    pub near: f32,
+    pub far: f32,
+    /// Brightness of the projected image.
+    pub intensity: f32,
+}
+
+impl VideoProjector {
+    pub fn new(player: Entity) -> Self {
+        VideoProjector {
+            player,
+            fov: std::f32::consts::FRAC_PI_6,
+            aspect: None,
+            near: 0.1,
+            far: 100.0,
+            intensity: 1.0,
+        }
+    }
+}
+
+/// Marks a mesh with a `VideoProjectorMaterial` as lit by `projector`.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct VideoProjectorReceiver {
+    pub projector: Entity,
+}
+
+/// Registers the projector material when the PBR renderer is present.
+pub(crate) fn build_video_projectors(app: &mut App) {
+    if !app.is_plugin_added::<PbrPlugin>() {
+        return;
+    }
+    load_internal_asset!(
+        app,
+        PROJECTOR_SHADER_HANDLE,
+        "projector.wgsl",
+        Shader::from_wgsl
+    );
+    app.add_plugins(MaterialPlugin::<VideoProjectorMaterial>::default());
+}
+
+/// Points each receiver's material at its projector's current frame and
+/// pose.
+pub fn update_video_projectors(
+    receivers: Query<(&VideoProjectorReceiver, &Handle<VideoProjectorMaterial>)>,
+    projectors: Query<(&VideoProjector, &GlobalTransform)>,
+    players: Query<&VideoPlayer>,
+    materials: Option<ResMut<Assets<VideoProjectorMaterial>>>,
+) {
+    let Some(mut materials) = materials else {
+        return;
+    };
+    for (receiver, handle) in receivers.iter() {
+        let Ok((projector, transform)) = projectors.get(receiver.projector) else {
+            continue;
+        };
+        let Ok(video_player) = players.get(projector.player) else {
+            continue;
+        };
+        let aspect = projector
+            .aspect
+            .unwrap_or_else(|| match video_player.native_size {
+                Some(size) if size.y > 0 => size.x as f32 / size.y as f32,
+                _ => 16.0 / 9.0,
+            });
+        let view_proj = Mat4::perspective_rh(projector.fov, aspect, projector.near, projector.far)
+            * transform.compute_matrix().inverse();
+        let frame = video_player.displayed_texture();
+        let stale = materials.get(handle).is_some_and(|material| {
+            material.extension.frame != frame
+                || material.extension.view_proj != view_proj
+                || material.extension.intensity != projector.intensity
+        });
+        if stale {
+            if let Some(material) = materials.get_mut(handle) {
+                material.extension.view_proj = view_proj;
+                material.extension.intensity = projector.intensity;
+                material.extension.frame = frame;
+            }
+        }
+    }
+}
@@ -0,0 +1,39 @@
+#import bevy_pbr::{
+    forward_io::{VertexOutput, FragmentOutput},
+    pbr_fragment::pbr_input_from_standard_material,
+    pbr_functions::{alpha_discard, apply_pbr_lighting, main_pass_post_lighting_processing},
+}
+
+struct VideoProjector {
+    view_proj: mat4x4<f32>,
+    intensity: f32,
+}
+
+@group(2) @binding(100) var<uniform> projector: VideoProjector;
+@group(2) @binding(101) var projector_texture: texture_2d<f32>;
+@group(2) @binding(102) var projector_sampler: sampler;
+
+@fragment
+fn fragment(
+    in: VertexOutput,
+    @builtin(front_facing) is_front: bool,
+) -> FragmentOutput {
+    var pbr_input = pbr_input_from_standard_material(in, is_front);
+    pbr_input.material.base_color = alpha_discard(pbr_input.material, pbr_input.material.base_color);
+
+    var out: FragmentOutput;
+    out.color = apply_pbr_lighting(pbr_input);
+
+    // Light from the projector is added on top of the lit surface, only in
+    // front of it and inside its frustum.
+    let clip = projector.view_proj * in.world_position;
+    let ndc = clip.xyz / clip.w;
+    if clip.w > 0.0 && all(abs(ndc.xy) <= vec2(1.0)) && ndc.z >= 0.0 && ndc.z <= 1.0 {
+        let uv = vec2(ndc.x * 0.5 + 0.5, 0.5 - ndc.y * 0.5);
+        let frame = textureSampleLevel(projector_texture, projector_sampler, uv, 0.0);
+        out.color = vec4(out.color.rgb + frame.rgb * pbr_input.material.base_color.rgb * projector.intensity, out.color.a);
+    }
+
+    out.color = main_pass_post_lighting_processing(pbr_input, out.color);
+    return out;
+}
@@ -0,0 +1,300 @@
+extern crate gstreamer as gst;
+extern crate gstreamer_app as gst_app;
+extern crate gstreamer_video as gst_video;
+
+use std::{
+    fmt,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use bevy::{
+    prelude::*,
+    render::{render_resource::TextureFormat, view::screenshot::ScreenshotManager},
+    window::PrimaryWindow,
+};
+use gst::prelude::*;
+
+use crate::init::ensure_gstreamer;
+
+#[derive(Debug)]
+pub enum RecordError {
+    Pipeline(String),
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecordError::Pipeline(err) => write!(f, "recording pipeline failed: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for RecordError {}
+
+/// Where a recording goes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordOutput {
+    /// MP4 file, for replays and clip export.
+    File(PathBuf),
+    /// Live stream to an RTMP ingest URL, stream key included.
+    Rtmp(String),
+    /// Live WebRTC stream to a WHIP endpoint. `whipclientsink` negotiates
+    /// and encodes itself, so the encoder settings are not used.
+    Whip(String),
+}
+
+/// How a recording is encoded and where it goes.
+#[derive(Debug, Clone)]
+pub struct RecordSettings {
+    pub output: RecordOutput,
+    /// Frames captured per second; the game may render faster.
+    pub framerate: u32,
+    /// Encoder element with its properties.
+    pub video_encoder: String,
+    /// Source of the audio track, e.g. `pulsesrc device=<sink>.monitor`.
+    /// `bevy_audio` mixes inside rodio, so game audio can only be recorded
+    /// from a loopback of the output device.
+    pub audio_source: Option<String>,
+    pub audio_encoder: String,
+}
+
+impl RecordSettings {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        RecordSettings::with_output(RecordOutput::File(path.into()))
+    }
+
+    /// Streams to RTMP with a keyframe every two seconds, as most ingest
+    /// servers require.
+    pub fn rtmp(url: impl Into<String>) -> Self {
+        let mut settings = RecordSettings::with_output(RecordOutput::Rtmp(url.into()));
+        settings.video_encoder = format!(
+            "{} key-int-max={}",
+            settings.video_encoder,
+            settings.framerate * 2
+        );
+        settings
+    }
+
+    pub fn whip(endpoint: impl Into<String>) -> Self {
+        RecordSettings::with_output(RecordOutput::Whip(endpoint.into()))
+    }
+
+    fn with_output(output: RecordOutput) -> Self {
+        RecordSettings {
+            output,
+            framerate: 30,
+            video_encoder: "x264enc tune=zerolatency speed-preset=ultrafast".to_string(),
+            audio_source: None,
+            audio_encoder: "avenc_aac".to_string(),
+        }
+    }
+
+    fn launch(&self) -> String {
+        let (video_encoder, audio_encoder) = (&self.video_encoder, &self.audio_encoder);
+        let (video, audio, sink) = match &self.output {
+            RecordOutput::File(path) => (
+                format!("{video_encoder} ! h264parse"),
+                audio_encoder.clone(),
+                format!("mp4mux name=mux ! filesink location=\"{}\"", path.display()),
+            ),
+            RecordOutput::Rtmp(url) => (
+                format!("{video_encoder} ! h264parse"),
+                format!("{audio_encoder} ! aacparse"),
+                format!("flvmux name=mux streamable=true ! rtmp2sink location=\"{url}\""),
+            ),
+            RecordOutput::Whip(endpoint) => (
+                "identity".to_string(),
+                "identity".to_string(),
+                format!("whipclientsink name=mux signaller::whip-endpoint=\"{endpoint}\""),
+            ),
+        };
+        let audio = self
+            .audio_source
+            .as_ref()
+            .map(|source| {
+                format!("{source} ! queue ! audioconvert ! audioresample ! {audio} ! queue ! mux.")
+            })
+            .unwrap_or_default();
+        format!(
+            "appsrc name=record_src is-live=true format=time ! queue ! videoconvert ! \
+            videoscale ! capsfilter name=record_size ! {video} ! queue ! mux. {sink} {audio}"
+        )
+    }
+}
+
+/// Feeds captured frames into the pipeline; cloned into screenshot
+/// callbacks.
+#[derive(Clone)]
+struct RecordInput {
+    pipeline: gst::Pipeline,
+    appsrc: gst_app::AppSrc,
+    framerate: u32,
+    /// Size of the first frame; later frames are scaled to it.
+    size: Arc<Mutex<Option<UVec2>>>,
+}
+
+impl RecordInput {
+    /// Encodes a captured window frame taken `timestamp` after the start.
+    fn push_frame(&self, image: Image, timestamp: Duration) {
+        let format = match image.texture_descriptor.format {
+            TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => {
+                gst_video::VideoFormat::Bgra
+            }
+            TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => {
+                gst_video::VideoFormat::Rgba
+            }
+            other => {
+                warn!("Cannot record window format {other:?}");
+                return;
+            }
+        };
+        let (width, height) = (image.width(), image.height());
+        if let Ok(mut size) = self.size.lock() {
+            if size.is_none() {
+                if let Some(filter) = self.pipeline.by_name("record_size") {
+                    let caps = gst_video::VideoCapsBuilder::new()
+                        .width(width as i32)
+                        .height(height as i32)
+                        .build();
+                    filter.set_property("caps", caps);
+                }
+                *size = Some(UVec2::new(width, height));
+            }
+        }
+        let caps = gst_video::VideoCapsBuilder::new()
+            .format(format)
+            .width(width as i32)
+            .height(height as i32)
+            .framerate(gst::Fraction::new(self.framerate as i32, 1))
+            .build();
+        if self.appsrc.caps().as_ref() != Some(&caps) {
+            self.appsrc.set_caps(Some(&caps));
+        }
+        let mut buffer = gst::Buffer::from_mut_slice(image.data);
+        if let Some(buffer) = buffer.get_mut() {
+            buffer.set_pts(gst::ClockTime::from_nseconds(timestamp.as_nanos() as u64));
+        }
+        if let Err(err) = self.appsrc.push_buffer(buffer) {
+            warn!("Failed to push recorded frame: {err}");
+        }
+    }
+}
+
+/// Records the primary window, UI included, into an MP4 file or a live
+/// stream. Insert it to start and remove it to stop, which finalizes the
+/// file or ends the stream; take it out with `World::remove_resource` and call
+/// `finish` to choose the timeout and see errors.
+#[derive(Resource)]
+pub struct VideoRecording {
+    input: RecordInput,
+    settings: RecordSettings,
+    started: Instant,
+    next_capture: Duration,
+    finished: bool,
+}
+
+impl VideoRecording {
+    pub fn start(settings: RecordSettings) -> Result<Self, RecordError> {
+        ensure_gstreamer().map_err(RecordError::Pipeline)?;
+        let pipeline = gst::parse::launch(&settings.launch())
+            .map_err(|err| RecordError::Pipeline(err.to_string()))?
+            .downcast::<gst::Pipeline>()
+            .map_err(|_| RecordError::Pipeline("expected a gst::Pipeline".to_string()))?;
+        let appsrc = pipeline
+            .by_name("record_src")
+            .and_then(|src| src.downcast::<gst_app::AppSrc>().ok())
+            .ok_or_else(|| RecordError::Pipeline("appsrc not found".to_string()))?;
+        pipeline
+            .set_state(gst::State::Playing)
+            .map_err(|err| RecordError::Pipeline(err.to_string()))?;
+        Ok(VideoRecording {
+            input: RecordInput {
+                pipeline,
+                appsrc,
+                framerate: settings.framerate.max(1),
+                size: Arc::new(Mutex::new(None)),
+            },
+            settings,
+            started: Instant::now(),
+            next_capture: Duration::ZERO,
+            finished: false,
+        })
+    }
+
+    pub fn settings(&self) -> &RecordSettings {
+        &self.settings
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Ends the streams and waits up to `timeout` for the muxer to write
+    /// the file's index. Without it the file is unplayable.
+    pub fn finish(mut self, timeout: Duration) -> Result<(), RecordError> {
+        self.finished = true;
+        self.finalize(timeout)
+    }
+
+    fn finalize(&self, timeout: Duration) -> Result<(), RecordError> {
+        let pipeline = &self.input.pipeline;
+        let _ = self.input.appsrc.end_of_stream();
+        // Live audio sources never end on their own.
+        pipeline.send_event(gst::event::Eos::new());
+        let result = match pipeline.bus() {
+            Some(bus) => match bus.timed_pop_filtered(
+                gst::ClockTime::from_nseconds(timeout.as_nanos() as u64),
+                &[gst::MessageType::Eos, gst::MessageType::Error],
+            ) {
+                Some(message) => match message.view() {
+                    gst::MessageView::Error(err) => {
+                        Err(RecordError::Pipeline(err.error().to_string()))
+                    }
+                    _ => Ok(()),
+                },
+                None => Err(RecordError::Pipeline("timed out finalizing".to_string())),
+            },
+            None => Ok(()),
+        };
+        let _ = pipeline.set_state(gst::State::Null);
+        result
+    }
+}
+
+impl Drop for VideoRecording {
+    fn drop(&mut self) {
+        if !self.finished {
+            if let Err(err) = self.finalize(Duration::from_secs(5)) {
+                error!("{err}");
+            }
+        }
+    }
+}
+
+/// Captures the primary window at the recording's framerate. Frames arrive
+/// on a task pool thread a frame or two later, timestamped at request time.
+pub fn capture_video_recording(
+    mut recording: ResMut<VideoRecording>,
+    screenshots: Option<ResMut<ScreenshotManager>>,
+    window: Query<Entity, With<PrimaryWindow>>,
+) {
+    let (Some(mut screenshots), Ok(window)) = (screenshots, window.get_single()) else {
+        return;
+    };
+    let elapsed = recording.elapsed();
+    if elapsed < recording.next_capture {
+        return;
+    }
+    let interval = Duration::from_secs(1) / recording.input.framerate;
+    recording.next_capture += interval;
+    // Skip frames after a hitch instead of bursting captures.
+    if recording.next_capture < elapsed {
+        recording.next_capture = elapsed + interval;
+    }
+    let input = recording.input.clone();
+    let _ = screenshots.take_screenshot(window, move |image| {
+        input.push_frame(image, elapsed);
+    });
+}
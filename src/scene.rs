@@ -0,0 +1,110 @@
+use bevy::prelude::*;
+
+use crate::{
+    plugin::{video_image_bundle, LoopMode, VideoFit, VideoPlayer},
+    video::VideoSource,
+};
+
+/// The user-facing settings of a player in a form Bevy scenes can save and
+/// load. Put it on a player entity and it follows the player's settings;
+/// leave `VideoPlayer` out of the scene with
+/// `DynamicSceneBuilder::deny::<VideoPlayer>()`. When a scene with it is
+/// spawned, the entity gets a node and a fresh `VideoPlayer` whose pipeline
+/// is built as usual.
+#[derive(Component, Reflect, Debug, Clone, PartialEq)]
+#[reflect(Component)]
+pub struct VideoPlayerConfig {
+    pub source: VideoSource,
+    pub width: f32,
+    pub height: f32,
+    pub fit: VideoFit,
+    pub loop_mode: LoopMode,
+    pub volume: f32,
+    /// Start playing once loaded.
+    pub autoplay: bool,
+    /// Cleared on load until the volume reaches the new pipeline; until
+    /// then the config's volume wins over the player's.
+    #[reflect(ignore)]
+    volume_applied: bool,
+}
+
+impl Default for VideoPlayerConfig {
+    fn default() -> Self {
+        VideoPlayerConfig {
+            source: VideoSource::default(),
+            width: 500.0,
+            height: 500.0,
+            fit: VideoFit::default(),
+            loop_mode: LoopMode::default(),
+            volume: 1.0,
+            autoplay: false,
+            volume_applied: true,
+        }
+    }
+}
+
+impl VideoPlayerConfig {
+    pub fn new(source: impl Into<VideoSource>, width: f32, height: f32) -> Self {
+        VideoPlayerConfig {
+            source: source.into(),
+            width,
+            height,
+            ..default()
+        }
+    }
+}
+
+/// Creates players for configs spawned without one, e.g. from a scene.
+pub fn load_video_player_configs(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut VideoPlayerConfig, Has<UiImage>), Without<VideoPlayer>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    for (entity, mut config, has_image) in query.iter_mut() {
+        config.volume_applied = false;
+        let video_player = VideoPlayer {
+            id: Some(entity),
+            source: config.source.clone(),
+            width: config.width,
+            height: config.height,
+            fit: config.fit,
+            loop_mode: config.loop_mode,
+            autoplay: config.autoplay,
+            ..default()
+        };
+        let mut entity_commands = commands.entity(entity);
+        if !has_image {
+            entity_commands.insert(video_image_bundle(
+                &mut images,
+                Vec2::new(config.width, config.height),
+                1.0,
+            ));
+        }
+        entity_commands.insert(video_player);
+    }
+}
+
+/// Applies the loaded volume once the pipeline exists, then mirrors the
+/// player's settings into its config so saved scenes are current.
+pub fn sync_video_player_configs(mut query: Query<(&VideoPlayer, &mut VideoPlayerConfig)>) {
+    for (video_player, mut config) in query.iter_mut() {
+        let mut current = config.clone();
+        current.source = video_player.source.clone();
+        current.width = video_player.width;
+        current.height = video_player.height;
+        current.fit = video_player.fit;
+        current.loop_mode = video_player.loop_mode;
+        current.autoplay = video_player.autoplay;
+        if let Some(pipeline) = video_player.pipeline.as_ref() {
+            if current.volume_applied {
+                current.volume = pipeline.volume();
+            } else {
+                pipeline.set_volume(current.volume);
+                current.volume_applied = true;
+            }
+        }
+        if current != *config {
+            *config = current;
+        }
+    }
+}
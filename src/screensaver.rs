@@ -0,0 +1,136 @@
+use std::time::Duration;
+
+use bevy::{
+    input::{
+        keyboard::KeyboardInput, mouse::MouseButtonInput, mouse::MouseWheel, touch::TouchInput,
+    },
+    prelude::*,
+    window::PrimaryWindow,
+};
+
+use crate::{
+    plugin::{insert_video_component_scaled, ui_scale_factor, VideoEnded, VideoPlayer},
+    video::VideoSource,
+};
+
+/// Kiosk attract loop: after `idle_after` without input, plays `playlist`
+/// full-window, looping over it, and despawns it on the next input. Insert
+/// the resource to enable it.
+#[derive(Resource, Debug, Clone)]
+pub struct VideoScreensaver {
+    pub idle_after: Duration,
+    pub playlist: Vec<VideoSource>,
+    /// The attract-loop player while it is showing.
+    pub player: Option<Entity>,
+    idle: Duration,
+    index: usize,
+}
+
+impl VideoScreensaver {
+    pub fn new(idle_after: Duration, playlist: Vec<VideoSource>) -> Self {
+        VideoScreensaver {
+            idle_after,
+            playlist,
+            player: None,
+            idle: Duration::ZERO,
+            index: 0,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.player.is_some()
+    }
+}
+
+fn spawn_screensaver_player(
+    commands: &mut Commands,
+    images: ResMut<Assets<Image>>,
+    window: Option<&Window>,
+    ui_scale: &UiScale,
+    source: VideoSource,
+) -> Entity {
+    let size = window
+        .map(|window| Vec2::new(window.width(), window.height()))
+        .unwrap_or(Vec2::new(500.0, 500.0));
+    let mut bundle = insert_video_component_scaled(images, size, ui_scale_factor(window, ui_scale));
+    bundle.style.position_type = PositionType::Absolute;
+    bundle.z_index = ZIndex::Global(i32::MAX);
+    let entity = commands.spawn(bundle).id();
+    let mut video_player = VideoPlayer {
+        source,
+        width: size.x,
+        height: size.y,
+        id: Some(entity),
+        ..Default::default()
+    };
+    video_player.play();
+    commands.entity(entity).insert(video_player);
+    entity
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn run_video_screensaver(
+    mut commands: Commands,
+    mut screensaver: ResMut<VideoScreensaver>,
+    mut keys: EventReader<KeyboardInput>,
+    mut buttons: EventReader<MouseButtonInput>,
+    mut cursor: EventReader<CursorMoved>,
+    mut wheel: EventReader<MouseWheel>,
+    mut touches: EventReader<TouchInput>,
+    mut ended: EventReader<VideoEnded>,
+    images: ResMut<Assets<Image>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    ui_scale: Res<UiScale>,
+    time: Res<Time>,
+) {
+    let input = keys.read().count()
+        + buttons.read().count()
+        + cursor.read().count()
+        + wheel.read().count()
+        + touches.read().count()
+        > 0;
+    let finished = ended
+        .read()
+        .any(|ended| Some(ended.entity) == screensaver.player);
+    if input {
+        screensaver.idle = Duration::ZERO;
+        if let Some(player) = screensaver.player.take() {
+            commands.entity(player).despawn_recursive();
+        }
+        return;
+    }
+    if screensaver.playlist.is_empty() {
+        return;
+    }
+    let window = windows.get_single().ok();
+    if finished {
+        if let Some(player) = screensaver.player.take() {
+            commands.entity(player).despawn_recursive();
+        }
+        screensaver.index = (screensaver.index + 1) % screensaver.playlist.len();
+        let source = screensaver.playlist[screensaver.index].clone();
+        screensaver.player = Some(spawn_screensaver_player(
+            &mut commands,
+            images,
+            window,
+            &ui_scale,
+            source,
+        ));
+        return;
+    }
+    if screensaver.is_active() {
+        return;
+    }
+    screensaver.idle += time.delta();
+    if screensaver.idle >= screensaver.idle_after {
+        screensaver.index = 0;
+        let source = screensaver.playlist[0].clone();
+        screensaver.player = Some(spawn_screensaver_player(
+            &mut commands,
+            images,
+            window,
+            &ui_scale,
+            source,
+        ));
+    }
+}
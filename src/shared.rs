@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::{
+    hdr::VideoTransfer,
+    plugin::{VideoCommand, VideoPlayer, VideoState},
+    video::VideoSource,
+};
+
+/// What followers copy from the player decoding a shared source.
+struct LeaderFrame {
+    texture: Option<Handle<Image>>,
+    native_size: Option<UVec2>,
+    has_alpha: bool,
+    transfer: VideoTransfer,
+    state: VideoState,
+}
+
+/// Decodes each source of players with `shared` set once. The first player
+/// with a pipeline leads; the others show its texture, follow its state and
+/// forward their commands to it. When the leader goes away another shared
+/// player takes over and builds a pipeline.
+pub fn share_video_pipelines(mut query: Query<(Entity, &mut VideoPlayer, &mut UiImage)>) {
+    let mut candidates: Vec<_> = query
+        .iter()
+        .filter(|(_, video_player, _)| video_player.shared)
+        .map(|(entity, video_player, _)| (video_player.pipeline.is_none(), entity))
+        .collect();
+    if candidates.is_empty() {
+        return;
+    }
+    candidates.sort();
+    let mut leaders: HashMap<VideoSource, Entity> = HashMap::new();
+    let mut frames = HashMap::new();
+    for (_, entity) in candidates {
+        let Ok((_, video_player, _)) = query.get(entity) else {
+            continue;
+        };
+        if leaders.contains_key(&video_player.source) {
+            continue;
+        }
+        leaders.insert(video_player.source.clone(), entity);
+        frames.insert(
+            entity,
+            LeaderFrame {
+                texture: video_player.current_frame.clone(),
+                native_size: video_player.native_size,
+                has_alpha: video_player.has_alpha,
+                transfer: video_player.transfer,
+                state: video_player.state(),
+            },
+        );
+    }
+
+    let mut forwarded: Vec<(Entity, Vec<VideoCommand>)> = Vec::new();
+    for (entity, mut video_player, mut image) in query.iter_mut() {
+        if !video_player.shared {
+            continue;
+        }
+        let Some(&leader) = leaders.get(&video_player.source) else {
+            continue;
+        };
+        if leader == entity {
+            // A follower taking over builds its own pipeline.
+            if video_player.shared_with.take().is_some() && video_player.pipeline.is_none() {
+                let playing = video_player.state() == VideoState::Playing;
+                video_player.runtime.state = VideoState::Init;
+                if playing {
+                    video_player.play();
+                }
+            }
+            continue;
+        }
+        video_player.shared_with = Some(leader);
+        if let Some(pipeline) = video_player.pipeline.take() {
+            pipeline.shutdown();
+        }
+        if !video_player.runtime.commands.is_empty() {
+            forwarded.push((leader, std::mem::take(&mut video_player.runtime.commands)));
+        }
+        let frame = &frames[&leader];
+        video_player.runtime.state = match frame.state {
+            VideoState::Init | VideoState::Ready => VideoState::Ready,
+            state => state,
+        };
+        video_player.native_size = frame.native_size;
+        video_player.has_alpha = frame.has_alpha;
+        video_player.transfer = frame.transfer;
+        if let Some(texture) = frame.texture.as_ref() {
+            if image.texture != *texture {
+                image.texture = texture.clone();
+            }
+            video_player.current_frame = Some(texture.clone());
+        }
+    }
+    for (leader, commands) in forwarded {
+        if let Ok((_, mut video_player, _)) = query.get_mut(leader) {
+            video_player.runtime.commands.extend(commands);
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use std::{collections::HashMap, time::Duration};
+
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::plugin::{VideoPlayer, VideoState};
+
+/// Familiar player keys. Insert this resource to enable them; they act on
+/// the player marked `FocusedVideo`, or on the only player if there is one.
+#[derive(Resource, Debug, Clone)]
+pub struct VideoShortcuts {
+    pub play_pause: KeyCode,
+    pub seek_back: KeyCode,
+    pub seek_forward: KeyCode,
+    pub seek_step: Duration,
+    pub mute: KeyCode,
+    /// Grows the player's node to cover the window and back.
+    pub fullscreen: KeyCode,
+    /// Writes the player's pipeline graph to a dot file in the temp
+    /// directory and logs where. Off by default.
+    pub dump_graph: Option<KeyCode>,
+    muted: HashMap<Entity, f32>,
+    fullscreen_players: HashMap<Entity, (Vec2, Style, ZIndex)>,
+}
+
+impl Default for VideoShortcuts {
+    fn default() -> Self {
+        VideoShortcuts {
+            play_pause: KeyCode::Space,
+            seek_back: KeyCode::ArrowLeft,
+            seek_forward: KeyCode::ArrowRight,
+            seek_step: Duration::from_secs(5),
+            mute: KeyCode::KeyM,
+            fullscreen: KeyCode::KeyF,
+            dump_graph: None,
+            muted: HashMap::new(),
+            fullscreen_players: HashMap::new(),
+        }
+    }
+}
+
+/// Marks the player keyboard shortcuts apply to.
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct FocusedVideo;
+
+fn toggle_fullscreen(
+    shortcuts: &mut VideoShortcuts,
+    entity: Entity,
+    video_player: &mut VideoPlayer,
+    style: &mut Style,
+    z_index: &mut ZIndex,
+    window: Option<&Window>,
+) {
+    if let Some((size, previous_style, previous_z)) = shortcuts.fullscreen_players.remove(&entity) {
+        video_player.width = size.x;
+        video_player.height = size.y;
+        *style = previous_style;
+        *z_index = previous_z;
+        return;
+    }
+    let Some(window) = window else {
+        return;
+    };
+    shortcuts.fullscreen_players.insert(
+        entity,
+        (
+            Vec2::new(video_player.width, video_player.height),
+            style.clone(),
+            *z_index,
+        ),
+    );
+    video_player.width = window.width();
+    video_player.height = window.height();
+    style.position_type = PositionType::Absolute;
+    style.left = Val::Px(0.0);
+    style.top = Val::Px(0.0);
+    *z_index = ZIndex::Global(i32::MAX);
+}
+
+pub fn video_keyboard_shortcuts(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut shortcuts: ResMut<VideoShortcuts>,
+    mut players: Query<(
+        Entity,
+        &mut VideoPlayer,
+        &mut Style,
+        &mut ZIndex,
+        Has<FocusedVideo>,
+    )>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+) {
+    let target = players
+        .iter()
+        .find(|(.., focused)| *focused)
+        .or_else(|| players.get_single().ok())
+        .map(|(entity, ..)| entity);
+    let Some(target) = target else {
+        return;
+    };
+    let Ok((entity, mut video_player, mut style, mut z_index, _)) = players.get_mut(target) else {
+        return;
+    };
+    if keys.just_pressed(shortcuts.play_pause) {
+        if video_player.state() == VideoState::Playing {
+            video_player.pause();
+        } else {
+            video_player.play();
+        }
+    }
+    let seek_back = keys.just_pressed(shortcuts.seek_back);
+    if seek_back || keys.just_pressed(shortcuts.seek_forward) {
+        let shared = video_player.share();
+        let position = shared.as_ref().and_then(|shared| shared.position());
+        if let Some(position) = position {
+            let target = if seek_back {
+                position.saturating_sub(shortcuts.seek_step)
+            } else {
+                let target = position + shortcuts.seek_step;
+                match shared.and_then(|shared| shared.duration()) {
+                    Some(duration) => target.min(duration),
+                    None => target,
+                }
+            };
+            video_player.seek(target);
+        }
+    }
+    if keys.just_pressed(shortcuts.mute) {
+        if let Some(pipeline) = video_player.pipeline.as_ref() {
+            match shortcuts.muted.remove(&entity) {
+                Some(volume) => pipeline.set_volume(volume),
+                None => {
+                    shortcuts.muted.insert(entity, pipeline.volume());
+                    pipeline.set_volume(0.0);
+                }
+            }
+        }
+    }
+    if shortcuts
+        .dump_graph
+        .is_some_and(|key| keys.just_pressed(key))
+    {
+        if let Some(pipeline) = video_player.pipeline.as_ref() {
+            let path = std::env::temp_dir().join(format!(
+                "bevy_gst_video-{}v{}.dot",
+                entity.index(),
+                entity.generation()
+            ));
+            match pipeline.dump_graph(&path) {
+                Ok(()) => info!("Wrote pipeline graph to {}", path.display()),
+                Err(err) => warn!("Failed to write pipeline graph: {err}"),
+            }
+        }
+    }
+    if keys.just_pressed(shortcuts.fullscreen) {
+        toggle_fullscreen(
+            &mut shortcuts,
+            entity,
+            &mut video_player,
+            &mut style,
+            &mut z_index,
+            windows.get_single().ok(),
+        );
+    }
+}
@@ -0,0 +1,58 @@
+use bevy::prelude::*;
+
+use crate::plugin::{insert_video_component, VideoPlayer};
+
+/// Shows the frames of `player` on this entity's mesh through its
+/// `StandardMaterial`'s base color texture. Make the material `unlit` to
+/// show the video at its own brightness.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct VideoMeshMaterial {
+    pub player: Entity,
+}
+
+/// Spawns `video_player` on a hidden UI node, for players only shown
+/// through meshes or materials. The node keeps rendering frames since
+/// `HiddenPolicy::Keep` is the default.
+pub fn spawn_offscreen_player(
+    commands: &mut Commands,
+    images: ResMut<Assets<Image>>,
+    video_player: VideoPlayer,
+) -> Entity {
+    let mut bundle = insert_video_component(images, Vec2::new(1.0, 1.0));
+    bundle.visibility = Visibility::Hidden;
+    bundle.style.position_type = PositionType::Absolute;
+    let entity = commands.spawn(bundle).id();
+    commands.entity(entity).insert(VideoPlayer {
+        id: Some(entity),
+        ..video_player
+    });
+    entity
+}
+
+/// Points each `VideoMeshMaterial`'s texture at its player's current frame.
+pub fn sync_video_mesh_materials(
+    query: Query<(&VideoMeshMaterial, &Handle<StandardMaterial>)>,
+    players: Query<&VideoPlayer>,
+    materials: Option<ResMut<Assets<StandardMaterial>>>,
+) {
+    let Some(mut materials) = materials else {
+        return;
+    };
+    for (surface, handle) in query.iter() {
+        let Some(frame) = players
+            .get(surface.player)
+            .ok()
+            .and_then(|video_player| video_player.displayed_texture())
+        else {
+            continue;
+        };
+        let stale = materials
+            .get(handle)
+            .is_some_and(|material| material.base_color_texture.as_ref() != Some(&frame));
+        if stale {
+            if let Some(material) = materials.get_mut(handle) {
+                material.base_color_texture = Some(frame);
+            }
+        }
+    }
+}
@@ -0,0 +1,275 @@
+//! Media fixtures for codec regression tests, enabled with the `test-media`
+//! feature. Fixtures are downloaded when they have a URL, otherwise encoded
+//! locally with GStreamer, and cached in `target/test-media` (override with
+//! `BEVY_GST_VIDEO_MEDIA_CACHE`).
+
+extern crate gstreamer as gst;
+extern crate gstreamer_app as gst_app;
+extern crate gstreamer_audio as gst_audio;
+
+use std::{
+    env, fmt, fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use byteorder::{ByteOrder, LittleEndian};
+use gst::prelude::*;
+
+use crate::{
+    init::ensure_gstreamer,
+    video::{video_sink_caps, VideoInfo},
+};
+
+pub struct MediaFixture {
+    pub name: &'static str,
+    pub file_name: &'static str,
+    /// Downloaded when set.
+    pub url: Option<&'static str>,
+    /// gst-launch description writing the file to `{path}`, used when there
+    /// is no URL.
+    pub encode: &'static str,
+    /// Elements the fixture needs; it is skipped when one is missing.
+    pub elements: &'static [&'static str],
+}
+
+pub const FIXTURES: &[MediaFixture] = &[
+    MediaFixture {
+        name: "H.264/AAC in MP4",
+        file_name: "h264_aac.mp4",
+        url: None,
+        encode: "videotestsrc num-buffers=90 ! video/x-raw,width=320,height=240,framerate=30/1 ! \
+                 x264enc ! h264parse ! mp4mux name=mux ! filesink location={path} \
+                 audiotestsrc num-buffers=130 ! audioconvert ! avenc_aac ! aacparse ! mux.",
+        elements: &["x264enc", "avenc_aac", "mp4mux"],
+    },
+    MediaFixture {
+        name: "VP9/Opus in WebM",
+        file_name: "vp9_opus.webm",
+        url: None,
+        encode: "videotestsrc num-buffers=90 ! video/x-raw,width=320,height=240,framerate=30/1 ! \
+                 vp9enc deadline=1 ! webmmux name=mux ! filesink location={path} \
+                 audiotestsrc num-buffers=130 ! audioconvert ! audioresample ! opusenc ! mux.",
+        elements: &["vp9enc", "opusenc", "webmmux"],
+    },
+    MediaFixture {
+        name: "HEVC in MP4",
+        file_name: "hevc.mp4",
+        url: None,
+        encode: "videotestsrc num-buffers=90 ! video/x-raw,width=320,height=240,framerate=30/1 ! \
+                 x265enc ! h265parse ! mp4mux ! filesink location={path}",
+        elements: &["x265enc", "mp4mux"],
+    },
+    MediaFixture {
+        name: "MJPEG in AVI",
+        file_name: "mjpeg.avi",
+        url: None,
+        encode: "videotestsrc num-buffers=90 ! video/x-raw,width=320,height=240,framerate=30/1 ! \
+                 jpegenc ! avimux ! filesink location={path}",
+        elements: &["jpegenc", "avimux"],
+    },
+    MediaFixture {
+        name: "Sintel trailer (VP8/Vorbis, network)",
+        file_name: "sintel_trailer-480p.webm",
+        url: Some("https://gstreamer.freedesktop.org/data/media/sintel_trailer-480p.webm"),
+        encode: "",
+        elements: &["souphttpsrc"],
+    },
+];
+
+#[derive(Debug)]
+pub enum FixtureError {
+    MissingElement(String),
+    Pipeline(String),
+    Io(std::io::Error),
+}
+
+impl fmt::Display for FixtureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FixtureError::MissingElement(name) => write!(f, "missing GStreamer element {name}"),
+            FixtureError::Pipeline(err) => write!(f, "pipeline failed: {err}"),
+            FixtureError::Io(err) => write!(f, "io error: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for FixtureError {}
+
+/// What the decode path produced for a file.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DecodeReport {
+    pub video_frames: usize,
+    pub width: u32,
+    pub height: u32,
+    pub audio_buffers: usize,
+    /// Largest absolute decoded sample; anything above 1.0 or non-finite
+    /// points at a broken decoder or sample format mismatch.
+    pub audio_peak: f32,
+}
+
+pub fn cache_dir() -> PathBuf {
+    env::var_os("BEVY_GST_VIDEO_MEDIA_CACHE")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| Path::new(env!("CARGO_MANIFEST_DIR")).join("target/test-media"))
+}
+
+fn run_to_eos(description: &str) -> Result<(), FixtureError> {
+    ensure_gstreamer().map_err(FixtureError::Pipeline)?;
+    let pipeline =
+        gst::parse::launch(description).map_err(|err| FixtureError::Pipeline(err.to_string()))?;
+    pipeline
+        .set_state(gst::State::Playing)
+        .map_err(|err| FixtureError::Pipeline(err.to_string()))?;
+    let bus = pipeline.bus().expect("Pipeline without bus");
+    let mut result = Ok(());
+    for msg in bus.iter_timed(gst::ClockTime::from_seconds(120)) {
+        use gst::MessageView;
+        match msg.view() {
+            MessageView::Eos(..) => break,
+            MessageView::Error(err) => {
+                result = Err(FixtureError::Pipeline(format!(
+                    "{} ({:?})",
+                    err.error(),
+                    err.debug()
+                )));
+                break;
+            }
+            _ => (),
+        }
+    }
+    let _ = pipeline.set_state(gst::State::Null);
+    result
+}
+
+impl MediaFixture {
+    pub fn path(&self) -> PathBuf {
+        cache_dir().join(self.file_name)
+    }
+
+    /// Returns the cached file, downloading or encoding it first if needed.
+    pub fn fetch(&self) -> Result<PathBuf, FixtureError> {
+        let path = self.path();
+        if path.exists() {
+            return Ok(path);
+        }
+        ensure_gstreamer().map_err(FixtureError::Pipeline)?;
+        if let Some(missing) = self
+            .elements
+            .iter()
+            .find(|name| gst::ElementFactory::find(name).is_none())
+        {
+            return Err(FixtureError::MissingElement(missing.to_string()));
+        }
+        fs::create_dir_all(cache_dir()).map_err(FixtureError::Io)?;
+        // Write to a temporary name so an interrupted run doesn't leave a
+        // truncated file in the cache.
+        let partial = path.with_extension("partial");
+        let location = partial.display().to_string();
+        let description = match self.url {
+            Some(url) => format!("souphttpsrc location={url} ! filesink location={location}"),
+            None => self.encode.replace("{path}", &location),
+        };
+        run_to_eos(&description)?;
+        fs::rename(&partial, &path).map_err(FixtureError::Io)?;
+        Ok(path)
+    }
+}
+
+/// Runs a file through the same conversion and caps the player uses and
+/// reports what came out.
+pub fn decode(path: &Path) -> Result<DecodeReport, FixtureError> {
+    ensure_gstreamer().map_err(FixtureError::Pipeline)?;
+    let uri = gst::glib::filename_to_uri(path, None)
+        .map_err(|err| FixtureError::Pipeline(err.to_string()))?;
+    let pipeline = gst::parse::launch(&format!(
+        "uridecodebin uri={uri} name=decodebin ! videoconvert ! appsink name=video_sink \
+         decodebin. ! audioconvert ! appsink name=audio_sink"
+    ))
+    .map_err(|err| FixtureError::Pipeline(err.to_string()))?
+    .downcast::<gst::Pipeline>()
+    .map_err(|_| FixtureError::Pipeline("expected a gst::Pipeline".to_string()))?;
+    let report = Arc::new(Mutex::new(DecodeReport::default()));
+
+    if let Some(sink) = pipeline
+        .by_name("video_sink")
+        .and_then(|sink| sink.downcast::<gst_app::AppSink>().ok())
+    {
+        sink.set_property("sync", false);
+        sink.set_caps(Some(&video_sink_caps()));
+        let report = Arc::clone(&report);
+        sink.set_callbacks(
+            gst_app::AppSinkCallbacks::builder()
+                .new_sample(move |sink| {
+                    let sample = sink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+                    if let Some(frame) = VideoInfo::from_sample(&sample) {
+                        let mut report = report.lock().expect("report error");
+                        report.video_frames += 1;
+                        report.width = frame.width;
+                        report.height = frame.height;
+                    }
+                    Ok(gst::FlowSuccess::Ok)
+                })
+                .build(),
+        );
+    }
+    if let Some(sink) = pipeline
+        .by_name("audio_sink")
+        .and_then(|sink| sink.downcast::<gst_app::AppSink>().ok())
+    {
+        sink.set_property("sync", false);
+        sink.set_caps(Some(
+            &gst_audio::AudioCapsBuilder::new()
+                .format(gst_audio::AudioFormat::F32le)
+                .build(),
+        ));
+        let report = Arc::clone(&report);
+        sink.set_callbacks(
+            gst_app::AppSinkCallbacks::builder()
+                .new_sample(move |sink| {
+                    let sample = sink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+                    let buffer = sample.buffer().ok_or(gst::FlowError::Error)?;
+                    let map = buffer.map_readable().map_err(|_| gst::FlowError::Error)?;
+                    let mut samples = vec![0f32; map.len() / 4];
+                    LittleEndian::read_f32_into(&map[..samples.len() * 4], &mut samples);
+                    let mut report = report.lock().expect("report error");
+                    report.audio_buffers += 1;
+                    for sample in samples {
+                        let magnitude = if sample.is_finite() {
+                            sample.abs()
+                        } else {
+                            f32::INFINITY
+                        };
+                        report.audio_peak = report.audio_peak.max(magnitude);
+                    }
+                    Ok(gst::FlowSuccess::Ok)
+                })
+                .build(),
+        );
+    }
+
+    pipeline
+        .set_state(gst::State::Playing)
+        .map_err(|err| FixtureError::Pipeline(err.to_string()))?;
+    let bus = pipeline.bus().expect("Pipeline without bus");
+    let mut result = Ok(());
+    for msg in bus.iter_timed(gst::ClockTime::from_seconds(60)) {
+        use gst::MessageView;
+        match msg.view() {
+            MessageView::Eos(..) => break,
+            MessageView::Error(err) => {
+                result = Err(FixtureError::Pipeline(format!(
+                    "{} ({:?})",
+                    err.error(),
+                    err.debug()
+                )));
+                break;
+            }
+            _ => (),
+        }
+    }
+    let _ = pipeline.set_state(gst::State::Null);
+    result?;
+    let report = *report.lock().expect("report error");
+    Ok(report)
+}
@@ -0,0 +1,70 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::{dirty::DirtyRegionUploads, plugin::VideoPlayer};
+
+/// A texture handle that never changes, for custom materials, particle
+/// systems and anything else that binds the video once. `VideoPlayer`
+/// allocates a new texture for every frame, which a material would have
+/// to be pointed at again each time.
+///
+/// Add it next to a `VideoPlayer`. The contents change once per presented
+/// frame, written during the render schedule of the frame it was presented
+/// in, so every draw in a frame sees the same picture. The GPU texture is
+/// overwritten in place while the frame size and format stay the same. When
+/// they change, the texture is reallocated and the asset sends
+/// `AssetEvent::Modified`; materials caching a bind group, which includes
+/// Bevy's in 0.13, need to be touched through `Assets::get_mut` to pick up
+/// the new texture. `VideoResolutionChanged` marks that moment.
+///
+/// The asset's CPU-side data holds the first frame at each size only.
+#[derive(Component, Debug, Clone)]
+pub struct VideoTexture {
+    handle: Handle<Image>,
+    presented: Option<(Duration, Duration)>,
+}
+
+impl VideoTexture {
+    /// Reserves the texture with a 1x1 placeholder, so it can be bound
+    /// before the first frame arrives.
+    pub fn new(images: &mut Assets<Image>) -> Self {
+        VideoTexture {
+            handle: images.add(Image::default()),
+            presented: None,
+        }
+    }
+
+    pub fn handle(&self) -> &Handle<Image> {
+        &self.handle
+    }
+}
+
+/// Copies newly presented frames into each player's `VideoTexture`.
+pub fn update_video_textures(
+    mut query: Query<(&VideoPlayer, &mut VideoTexture)>,
+    mut images: ResMut<Assets<Image>>,
+    mut uploads: ResMut<DirtyRegionUploads>,
+) {
+    for (video_player, mut texture) in query.iter_mut() {
+        let presented = video_player.presentation_anchor;
+        if presented.is_none() || presented == texture.presented {
+            continue;
+        }
+        let Some(frame) = video_player.snapshot(&images) else {
+            continue;
+        };
+        texture.presented = presented;
+        let same_layout = images.get(&texture.handle).is_some_and(|current| {
+            current.texture_descriptor.size == frame.texture_descriptor.size
+                && current.texture_descriptor.format == frame.texture_descriptor.format
+                && current.data.len() == frame.data.len()
+        });
+        if same_layout {
+            let whole = 0..frame.height();
+            uploads.push(texture.handle.id(), &frame, std::slice::from_ref(&whole));
+        } else {
+            images.insert(texture.handle.id(), frame);
+        }
+    }
+}
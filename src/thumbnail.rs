@@ -0,0 +1,207 @@
+extern crate gstreamer as gst;
+extern crate gstreamer_app as gst_app;
+
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use bevy::prelude::*;
+use gst::prelude::*;
+
+use crate::{
+    color::{apply_color_space, ColorSpace},
+    hdr::{HdrMode, VideoTransfer},
+    init::ensure_gstreamer,
+    plugin::frame_to_image,
+    video::{video_sink_caps, watch_transfer, VideoInfo},
+};
+
+#[derive(Debug)]
+pub enum ThumbnailError {
+    Pipeline(String),
+    Seek(String),
+    NoFrame,
+    Cancelled,
+}
+
+impl fmt::Display for ThumbnailError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThumbnailError::Pipeline(err) => write!(f, "thumbnail pipeline failed: {err}"),
+            ThumbnailError::Seek(err) => write!(f, "thumbnail seek failed: {err}"),
+            ThumbnailError::NoFrame => write!(f, "no frame decoded for thumbnail"),
+            ThumbnailError::Cancelled => write!(f, "thumbnail decoding was cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for ThumbnailError {}
+
+/// Grabs single frames from a URI without creating a `VideoPlayer`.
+pub struct VideoThumbnailer {
+    /// How long to wait for prerolling and seeking before giving up.
+    pub timeout: Duration,
+    /// Scale frames to this size instead of the native one.
+    pub size: Option<(u32, u32)>,
+}
+
+impl Default for VideoThumbnailer {
+    fn default() -> Self {
+        VideoThumbnailer {
+            timeout: Duration::from_secs(10),
+            size: None,
+        }
+    }
+}
+
+/// Appsink of a prerolled thumbnail pipeline and the stream properties its
+/// frames need.
+struct ThumbnailSource {
+    appsink: gst_app::AppSink,
+    transfer: Arc<Mutex<VideoTransfer>>,
+}
+
+impl VideoThumbnailer {
+    /// Decodes the frame at `timestamp` with the default timeout. Blocks the
+    /// calling thread; run it on a task pool for network sources.
+    pub fn grab(uri: &str, timestamp: Duration) -> Result<Image, ThumbnailError> {
+        VideoThumbnailer::default().grab_frame(uri, timestamp)
+    }
+
+    pub fn grab_frame(&self, uri: &str, timestamp: Duration) -> Result<Image, ThumbnailError> {
+        let info = self.grab_video_info(uri, timestamp)?;
+        frame_to_image(info, HdrMode::Tonemap).ok_or(ThumbnailError::NoFrame)
+    }
+
+    pub fn grab_video_info(
+        &self,
+        uri: &str,
+        timestamp: Duration,
+    ) -> Result<VideoInfo, ThumbnailError> {
+        let pipeline = self.pipeline(uri)?;
+        let result = self.preroll(&pipeline).and_then(|source| {
+            self.pull(
+                &pipeline,
+                &source,
+                timestamp,
+                gst::SeekFlags::FLUSH | gst::SeekFlags::ACCURATE,
+            )
+        });
+        let _ = pipeline.set_state(gst::State::Null);
+        result
+    }
+
+    /// Decodes `count` frames spread evenly over the whole stream, e.g. for
+    /// scrub bar previews. Seeks snap to keyframes to keep this fast.
+    pub fn grab_strip(
+        &self,
+        uri: &str,
+        count: usize,
+    ) -> Result<Vec<(Duration, VideoInfo)>, ThumbnailError> {
+        self.grab_strip_cancellable(uri, count, &AtomicBool::new(false))
+    }
+
+    /// Like `grab_strip`, but gives up with `ThumbnailError::Cancelled`
+    /// before the next frame once `cancelled` is set.
+    pub fn grab_strip_cancellable(
+        &self,
+        uri: &str,
+        count: usize,
+        cancelled: &AtomicBool,
+    ) -> Result<Vec<(Duration, VideoInfo)>, ThumbnailError> {
+        let pipeline = self.pipeline(uri)?;
+        let result = self.preroll(&pipeline).and_then(|source| {
+            let duration = pipeline
+                .query_duration::<gst::ClockTime>()
+                .map(|duration| Duration::from_nanos(duration.nseconds()))
+                .ok_or_else(|| ThumbnailError::Seek("unknown duration".to_string()))?;
+            (0..count)
+                .map(|index| {
+                    if cancelled.load(Ordering::SeqCst) {
+                        return Err(ThumbnailError::Cancelled);
+                    }
+                    let timestamp = duration.mul_f64((index as f64 + 0.5) / count as f64);
+                    let flags = gst::SeekFlags::FLUSH | gst::SeekFlags::KEY_UNIT;
+                    self.pull(&pipeline, &source, timestamp, flags)
+                        .map(|info| (timestamp, info))
+                })
+                .collect()
+        });
+        let _ = pipeline.set_state(gst::State::Null);
+        result
+    }
+
+    fn pipeline(&self, uri: &str) -> Result<gst::Pipeline, ThumbnailError> {
+        ensure_gstreamer().map_err(ThumbnailError::Pipeline)?;
+        gst::parse::launch(&format!(
+            "uridecodebin uri={uri} ! videoconvert name=thumbnail_convert ! \
+            videoflip video-direction=auto ! videoscale ! appsink name=thumbnail_sink"
+        ))
+        .map_err(|err| ThumbnailError::Pipeline(err.to_string()))?
+        .downcast::<gst::Pipeline>()
+        .map_err(|_| ThumbnailError::Pipeline("expected a gst::Pipeline".to_string()))
+    }
+
+    fn preroll(&self, pipeline: &gst::Pipeline) -> Result<ThumbnailSource, ThumbnailError> {
+        let appsink = pipeline
+            .by_name("thumbnail_sink")
+            .and_then(|sink| sink.downcast::<gst_app::AppSink>().ok())
+            .ok_or_else(|| ThumbnailError::Pipeline("appsink not found".to_string()))?;
+        appsink.set_property("sync", false);
+        let mut caps = video_sink_caps();
+        if let Some((width, height)) = self.size {
+            caps.make_mut().set("width", width as i32);
+            caps.make_mut().set("height", height as i32);
+        }
+        appsink.set_caps(Some(&caps));
+        let transfer = Arc::new(Mutex::new(VideoTransfer::default()));
+        if let Some(convert) = pipeline.by_name("thumbnail_convert") {
+            apply_color_space(&convert, ColorSpace::default());
+            watch_transfer(&convert, Arc::clone(&transfer));
+        }
+
+        pipeline
+            .set_state(gst::State::Paused)
+            .map_err(|err| ThumbnailError::Pipeline(err.to_string()))?;
+        let (result, _, _) = pipeline.state(self.clock_timeout());
+        result.map_err(|err| ThumbnailError::Pipeline(err.to_string()))?;
+        Ok(ThumbnailSource { appsink, transfer })
+    }
+
+    fn pull(
+        &self,
+        pipeline: &gst::Pipeline,
+        source: &ThumbnailSource,
+        timestamp: Duration,
+        flags: gst::SeekFlags,
+    ) -> Result<VideoInfo, ThumbnailError> {
+        let timeout = self.clock_timeout();
+        if !timestamp.is_zero() {
+            pipeline
+                .seek_simple(
+                    flags,
+                    gst::ClockTime::from_nseconds(timestamp.as_nanos() as u64),
+                )
+                .map_err(|err| ThumbnailError::Seek(err.to_string()))?;
+            let (result, _, _) = pipeline.state(timeout);
+            result.map_err(|err| ThumbnailError::Seek(err.to_string()))?;
+        }
+
+        let sample = source
+            .appsink
+            .try_pull_preroll(timeout)
+            .ok_or(ThumbnailError::NoFrame)?;
+        let mut info = VideoInfo::from_sample(&sample).ok_or(ThumbnailError::NoFrame)?;
+        info.transfer = source.transfer.lock().map(|t| *t).unwrap_or_default();
+        Ok(info)
+    }
+
+    fn clock_timeout(&self) -> gst::ClockTime {
+        gst::ClockTime::from_nseconds(self.timeout.as_nanos() as u64)
+    }
+}
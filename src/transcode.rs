@@ -0,0 +1,262 @@
+extern crate gstreamer as gst;
+
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use bevy::prelude::*;
+use gst::prelude::*;
+
+use crate::init::ensure_gstreamer;
+
+/// What to transcode and how.
+#[derive(Debug, Clone)]
+pub struct TranscodeSettings {
+    pub input: String,
+    pub output: PathBuf,
+    /// In and out points; `None` converts the whole stream.
+    pub range: Option<(Duration, Duration)>,
+    /// Encoder element with its properties.
+    pub video_encoder: String,
+    /// Audio encoder, or `None` to drop the audio. Must be `None` for
+    /// sources without audio, or the muxer waits for it forever.
+    pub audio_encoder: Option<String>,
+    pub muxer: String,
+    /// How long to wait for the source to preroll before giving up.
+    pub timeout: Duration,
+}
+
+impl TranscodeSettings {
+    /// H.264 and AAC in MP4.
+    pub fn new(input: impl Into<String>, output: impl Into<PathBuf>) -> Self {
+        TranscodeSettings {
+            input: input.into(),
+            output: output.into(),
+            range: None,
+            video_encoder: "x264enc".to_string(),
+            audio_encoder: Some("avenc_aac".to_string()),
+            muxer: "mp4mux".to_string(),
+            timeout: Duration::from_secs(10),
+        }
+    }
+
+    pub fn with_range(mut self, start: Duration, stop: Duration) -> Self {
+        self.range = Some((start, stop));
+        self
+    }
+
+    fn launch(&self) -> String {
+        let audio = self
+            .audio_encoder
+            .as_ref()
+            .map(|encoder| {
+                format!("decode. ! queue ! audioconvert ! audioresample ! {encoder} ! queue ! mux.")
+            })
+            .unwrap_or_default();
+        format!(
+            "uridecodebin uri={} name=decode \
+            decode. ! queue ! videoconvert ! {} ! queue ! mux. {audio} \
+            {} name=mux ! filesink location=\"{}\"",
+            self.input,
+            self.video_encoder,
+            self.muxer,
+            self.output.display()
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+enum TranscodeStatus {
+    #[default]
+    Pending,
+    Running(f32),
+    Done(Result<PathBuf, String>),
+}
+
+/// Converts or cuts a clip on a background thread, e.g. for a replay
+/// editor. Spawn an entity with it; progress arrives as
+/// `VideoTranscodeProgress` and the result as `VideoTranscodeFinished`,
+/// after which the entity is despawned. Despawning it earlier cancels the
+/// transcode.
+#[derive(Component)]
+pub struct VideoTranscode {
+    pub settings: TranscodeSettings,
+    status: Arc<Mutex<TranscodeStatus>>,
+    cancelled: Arc<AtomicBool>,
+    started: bool,
+    reported: f32,
+}
+
+impl VideoTranscode {
+    pub fn new(settings: TranscodeSettings) -> Self {
+        VideoTranscode {
+            settings,
+            status: Arc::new(Mutex::new(TranscodeStatus::default())),
+            cancelled: Arc::new(AtomicBool::new(false)),
+            started: false,
+            reported: -1.0,
+        }
+    }
+}
+
+impl Drop for VideoTranscode {
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct VideoTranscodeProgress {
+    pub entity: Entity,
+    /// Fraction of the range written, from 0 to 1.
+    pub progress: f32,
+}
+
+#[derive(Event, Debug, Clone)]
+pub struct VideoTranscodeFinished {
+    pub entity: Entity,
+    pub result: Result<PathBuf, String>,
+}
+
+fn to_clock(time: Duration) -> gst::ClockTime {
+    gst::ClockTime::from_nseconds(time.as_nanos() as u64)
+}
+
+fn transcode(
+    settings: &TranscodeSettings,
+    status: &Mutex<TranscodeStatus>,
+    cancelled: &AtomicBool,
+) -> Result<PathBuf, String> {
+    ensure_gstreamer()?;
+    let pipeline = gst::parse::launch(&settings.launch())
+        .map_err(|err| err.to_string())?
+        .downcast::<gst::Pipeline>()
+        .map_err(|_| "expected a gst::Pipeline".to_string())?;
+    let result = run_pipeline(&pipeline, settings, status, cancelled);
+    let _ = pipeline.set_state(gst::State::Null);
+    result.map(|()| settings.output.clone())
+}
+
+fn run_pipeline(
+    pipeline: &gst::Pipeline,
+    settings: &TranscodeSettings,
+    status: &Mutex<TranscodeStatus>,
+    cancelled: &AtomicBool,
+) -> Result<(), String> {
+    pipeline
+        .set_state(gst::State::Paused)
+        .map_err(|err| err.to_string())?;
+    let (result, _, _) = pipeline.state(to_clock(settings.timeout));
+    result.map_err(|err| err.to_string())?;
+    let duration = pipeline
+        .query_duration::<gst::ClockTime>()
+        .map(|duration| Duration::from_nanos(duration.nseconds()));
+    let (start, stop) = match settings.range {
+        Some((start, stop)) => {
+            pipeline
+                .seek(
+                    1.0,
+                    gst::SeekFlags::FLUSH | gst::SeekFlags::ACCURATE,
+                    gst::SeekType::Set,
+                    to_clock(start),
+                    gst::SeekType::Set,
+                    to_clock(stop),
+                )
+                .map_err(|err| err.to_string())?;
+            (start, Some(stop))
+        }
+        None => (Duration::ZERO, duration),
+    };
+    pipeline
+        .set_state(gst::State::Playing)
+        .map_err(|err| err.to_string())?;
+
+    let bus = pipeline.bus().ok_or("pipeline without bus")?;
+    loop {
+        if cancelled.load(Ordering::SeqCst) {
+            return Err("cancelled".to_string());
+        }
+        let message = bus.timed_pop_filtered(
+            gst::ClockTime::from_mseconds(100),
+            &[gst::MessageType::Eos, gst::MessageType::Error],
+        );
+        match message.as_ref().map(|message| message.view()) {
+            Some(gst::MessageView::Eos(..)) => return Ok(()),
+            Some(gst::MessageView::Error(err)) => return Err(err.error().to_string()),
+            _ => {}
+        }
+        let position = pipeline
+            .query_position::<gst::ClockTime>()
+            .map(|position| Duration::from_nanos(position.nseconds()));
+        if let (Some(position), Some(stop)) = (position, stop) {
+            let length = stop.saturating_sub(start).as_secs_f32();
+            if length > 0.0 {
+                let progress = position.saturating_sub(start).as_secs_f32() / length;
+                if let Ok(mut status) = status.lock() {
+                    *status = TranscodeStatus::Running(progress.clamp(0.0, 1.0));
+                }
+            }
+        }
+    }
+}
+
+/// Starts new transcodes on threads of their own and reports their
+/// progress. A transcode waits on the bus for its whole length, which
+/// would tie up one of the few task pool threads pipeline setup runs on.
+pub fn run_video_transcodes(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut VideoTranscode)>,
+    mut progress_events: EventWriter<VideoTranscodeProgress>,
+    mut finished_events: EventWriter<VideoTranscodeFinished>,
+) {
+    for (entity, mut transcode_job) in query.iter_mut() {
+        if !transcode_job.started {
+            transcode_job.started = true;
+            let settings = transcode_job.settings.clone();
+            let status = Arc::clone(&transcode_job.status);
+            let cancelled = Arc::clone(&transcode_job.cancelled);
+            let spawned = thread::Builder::new()
+                .name(format!("video-transcode-{entity:?}"))
+                .spawn(move || {
+                    let result = transcode(&settings, &status, &cancelled);
+                    if let Ok(mut status) = status.lock() {
+                        *status = TranscodeStatus::Done(result);
+                    }
+                });
+            if let Err(err) = spawned {
+                if let Ok(mut status) = transcode_job.status.lock() {
+                    *status = TranscodeStatus::Done(Err(err.to_string()));
+                }
+            }
+        }
+        let status = match transcode_job.status.lock() {
+            Ok(status) => status.clone(),
+            Err(_) => continue,
+        };
+        match status {
+            TranscodeStatus::Pending => {}
+            TranscodeStatus::Running(progress) => {
+                if progress != transcode_job.reported {
+                    transcode_job.reported = progress;
+                    progress_events.send(VideoTranscodeProgress { entity, progress });
+                }
+            }
+            TranscodeStatus::Done(result) => {
+                if result.is_ok() {
+                    progress_events.send(VideoTranscodeProgress {
+                        entity,
+                        progress: 1.0,
+                    });
+                }
+                finished_events.send(VideoTranscodeFinished { entity, result });
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+}
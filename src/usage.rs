@@ -0,0 +1,78 @@
+use std::{cmp::Reverse, collections::HashMap, time::Duration};
+
+use bevy::prelude::*;
+
+use crate::{plugin::VideoPlayer, video::QueuePolicy};
+
+/// Approximate resources held by a single player.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PlayerUsage {
+    pub queued_frames: usize,
+    /// Bytes held by decoded frames that have not been presented yet.
+    pub queued_bytes: usize,
+    /// Wall time spent in the player's sample callbacks since it started;
+    /// see `PlayerStats::callback_time`.
+    pub callback_time: Duration,
+    /// Policy the queue runs under and the frames it discarded so far.
+    pub queue_policy: QueuePolicy,
+    pub dropped_frames: u64,
+}
+
+/// Per-player usage, refreshed every frame by the plugin.
+#[derive(Resource, Debug, Default)]
+pub struct VideoUsage {
+    pub players: HashMap<Entity, PlayerUsage>,
+}
+
+impl VideoUsage {
+    pub fn get(&self, entity: Entity) -> Option<&PlayerUsage> {
+        self.players.get(&entity)
+    }
+
+    pub fn total_bytes(&self) -> usize {
+        self.players.values().map(|usage| usage.queued_bytes).sum()
+    }
+
+    pub fn total_callback_time(&self) -> Duration {
+        self.players.values().map(|usage| usage.callback_time).sum()
+    }
+
+    /// Players sorted by queued bytes, largest first.
+    pub fn heaviest_by_memory(&self) -> Vec<(Entity, PlayerUsage)> {
+        let mut players: Vec<_> = self.players.iter().map(|(e, u)| (*e, *u)).collect();
+        players.sort_by_key(|(_, usage)| Reverse(usage.queued_bytes));
+        players
+    }
+
+    /// Players sorted by callback time, largest first.
+    pub fn heaviest_by_callback_time(&self) -> Vec<(Entity, PlayerUsage)> {
+        let mut players: Vec<_> = self.players.iter().map(|(e, u)| (*e, *u)).collect();
+        players.sort_by_key(|(_, usage)| Reverse(usage.callback_time));
+        players
+    }
+}
+
+pub fn update_video_usage(query: Query<(Entity, &VideoPlayer)>, mut usage: ResMut<VideoUsage>) {
+    usage.players.clear();
+    for (entity, video_player) in query.iter() {
+        let Some(pipeline) = video_player.pipeline.as_ref() else {
+            continue;
+        };
+        let (queued_frames, queued_bytes) = pipeline.queued_frames();
+        let stats = pipeline
+            .stats
+            .lock()
+            .map(|stats| *stats)
+            .unwrap_or_default();
+        usage.players.insert(
+            entity,
+            PlayerUsage {
+                queued_frames,
+                queued_bytes,
+                callback_time: stats.callback_time,
+                queue_policy: video_player.queue_policy,
+                dropped_frames: stats.dropped_frames,
+            },
+        );
+    }
+}
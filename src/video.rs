@@ -4,214 +4,2272 @@ extern crate gstreamer_audio as gst_audio;
 extern crate gstreamer_video as gst_video;
 use std::{
     collections::VecDeque,
-    sync::{Arc, Mutex},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc, Arc, Mutex, RwLock,
+    },
+    time::{Duration, Instant},
 };
+#[cfg(feature = "audio")]
+use std::{sync::OnceLock, thread};
 
+use bevy::{
+    log::{debug, error, info_span, warn},
+    reflect::Reflect,
+};
+#[cfg(feature = "audio")]
 use byteorder::{ByteOrder, LittleEndian};
+use crossbeam_channel::{Receiver, Sender, TrySendError};
 use gst::{element_error, prelude::*};
 use gstreamer_video::VideoFrameExt;
-use rodio::OutputStream;
 
+#[cfg(feature = "audio")]
+use crate::audio::{STREAM_CHANNELS, STREAM_RATE};
+use crate::{
+    audio::{AudioEffects, Ducking, VideoAudioStream},
+    calibration::attach_calibration_sources,
+    color::{apply_color_space, ColorSpace},
+    gif::attach_gif_source,
+    hdr::VideoTransfer,
+    hls::{watch_hls, HlsState, HlsVariant, VariantSelection},
+    init::ensure_gstreamer,
+    missing::{install_hint, is_missing_plugin_error, parse_missing_plugin},
+    orientation::{watch_orientation, VideoOrientation},
+};
+#[cfg(feature = "audio")]
+use rodio::{OutputStream, OutputStreamHandle};
+
+/// Runs on the decode thread for every frame before it is queued, e.g. to
+/// scan QR codes or run object detection on webcam input. May modify the
+/// frame; keep it fast, since it delays the frames behind it.
+pub type FrameProcessor = Arc<dyn Fn(&mut VideoInfo) + Send + Sync>;
+
+/// Frames handed to `subscribe_frames` receivers, shared between them.
+type FrameSubscribers = Arc<Mutex<Vec<mpsc::SyncSender<Arc<VideoInfo>>>>>;
+
+#[derive(Clone)]
 pub struct VideoInfo {
     pub height: u32,
     pub width: u32,
     pub data: Vec<u8>,
     pub pts: u64,
+    /// Whether the fourth byte carries real alpha (RGBA) or padding (RGBx).
+    pub has_alpha: bool,
+    /// `Rgba`/`Rgbx` for 8-bit frames, `Rgba64Le` for deep color ones.
+    pub format: gst_video::VideoFormat,
+    /// Transfer function of the decoded stream before RGB conversion.
+    pub transfer: VideoTransfer,
 }
 
-#[derive(Clone)]
+/// Formats the video appsinks accept. RGBA comes first so sources with an
+/// alpha channel keep it; opaque sources may negotiate either, and 10/12-bit
+/// sources negotiate 16 bits per channel instead of banding.
+pub fn video_sink_caps() -> gst::Caps {
+    gst_video::VideoCapsBuilder::new()
+        .format_list([
+            gst_video::VideoFormat::Rgba,
+            gst_video::VideoFormat::Rgbx,
+            gst_video::VideoFormat::Rgba64Le,
+        ])
+        .build()
+}
+
+/// Tracks the transfer function of the caps flowing into `element`, which
+/// the RGB caps after conversion no longer carry.
+pub fn watch_transfer(element: &gst::Element, transfer: Arc<Mutex<VideoTransfer>>) {
+    let Some(pad) = element.static_pad("sink") else {
+        return;
+    };
+    pad.add_probe(gst::PadProbeType::EVENT_DOWNSTREAM, move |_, probe_info| {
+        if let Some(gst::PadProbeData::Event(event)) = probe_info.data.as_ref() {
+            if let gst::EventView::Caps(caps) = event.view() {
+                if let Ok(info) = gst_video::VideoInfo::from_caps(caps.caps()) {
+                    if let Ok(mut transfer) = transfer.lock() {
+                        *transfer = VideoTransfer::from_video_info(&info);
+                    }
+                }
+            }
+        }
+        gst::PadProbeReturn::Ok
+    });
+}
+
+impl VideoInfo {
+    /// Copies the first plane of a packed RGBA sample, dropping any row
+    /// padding. The transfer function is left as SDR.
+    pub fn from_sample(sample: &gst::Sample) -> Option<Self> {
+        let buffer = sample.buffer()?;
+        let info = gst_video::VideoInfo::from_caps(sample.caps()?).ok()?;
+        let frame = gst_video::VideoFrameRef::from_buffer_ref_readable(buffer, &info).ok()?;
+        let width = frame.width();
+        let height = frame.height();
+        let stride = frame.plane_stride()[0] as usize;
+        let row = width as usize * info.format_info().pixel_stride()[0] as usize;
+        let plane = frame.plane_data(0).ok()?;
+        let data = if stride == row {
+            plane.get(..row * height as usize)?.to_vec()
+        } else {
+            plane
+                .chunks(stride)
+                .take(height as usize)
+                .flat_map(|line| &line[..row])
+                .copied()
+                .collect()
+        };
+        Some(VideoInfo {
+            width,
+            height,
+            data,
+            pts: buffer.pts().map(|pts| pts.nseconds()).unwrap_or_default(),
+            has_alpha: info.format_info().has_alpha(),
+            format: info.format(),
+            transfer: VideoTransfer::Sdr,
+        })
+    }
+
+    /// Keeps only the `width` x `height` region at `(x, y)`, clamped to the
+    /// frame.
+    pub fn crop(&mut self, x: u32, y: u32, width: u32, height: u32) {
+        if self.width == 0 || self.height == 0 {
+            return;
+        }
+        let x = x.min(self.width - 1);
+        let y = y.min(self.height - 1);
+        let width = width.clamp(1, self.width - x);
+        let height = height.clamp(1, self.height - y);
+        if (x, y, width, height) == (0, 0, self.width, self.height) {
+            return;
+        }
+        let pixel = self.data.len() / (self.width as usize * self.height as usize);
+        let row = self.width as usize * pixel;
+        let start = x as usize * pixel;
+        let end = start + width as usize * pixel;
+        self.data = self
+            .data
+            .chunks_exact(row)
+            .skip(y as usize)
+            .take(height as usize)
+            .flat_map(|line| &line[start..end])
+            .copied()
+            .collect();
+        self.width = width;
+        self.height = height;
+    }
+}
+
+/// Certificate validation for HTTPS and RTSPS sources.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Reflect)]
+pub struct TlsOptions {
+    /// PEM file of the certificate authorities to trust instead of the
+    /// system's, e.g. for cameras behind a private CA. Only HTTPS sources
+    /// support it; RTSPS sources need `accept_invalid_certs` for now.
+    pub ca_file: Option<PathBuf>,
+    /// Accept any certificate, including self-signed and expired ones.
+    /// For development only: it makes the connection trivial to intercept.
+    pub accept_invalid_certs: bool,
+}
+
+impl TlsOptions {
+    pub fn is_empty(&self) -> bool {
+        self.ca_file.is_none() && !self.accept_invalid_certs
+    }
+
+    fn apply(&self, source: &gst::Element) {
+        if source.has_property("ssl-strict", None) {
+            if let Some(ca_file) = self.ca_file.as_ref() {
+                source.set_property("ssl-ca-file", ca_file.to_string_lossy().as_ref());
+            }
+            if self.accept_invalid_certs {
+                source.set_property("ssl-strict", false);
+            }
+        } else if source.has_property("tls-validation-flags", None) && self.accept_invalid_certs {
+            source.set_property_from_str("tls-validation-flags", "0");
+        }
+    }
+}
+
+/// HTTP options applied to `souphttpsrc`, whether uridecodebin creates it or
+/// it reads an MJPEG camera. The TLS options also apply to `rtspsrc`.
+#[derive(Debug, Clone, Default, Reflect)]
+pub struct NetworkOptions {
+    /// Extra request headers, e.g. `("Authorization", "Bearer ...")`.
+    pub headers: Vec<(String, String)>,
+    pub user_agent: Option<String>,
+    /// Proxy URI, e.g. `http://proxy.local:3128`.
+    pub proxy: Option<String>,
+    pub proxy_credentials: Option<(String, String)>,
+    /// Credentials for HTTP basic/digest authentication.
+    pub credentials: Option<(String, String)>,
+    /// Cookies in `name=value` form.
+    pub cookies: Vec<String>,
+    pub tls: TlsOptions,
+}
+
+impl NetworkOptions {
+    pub fn is_empty(&self) -> bool {
+        self.headers.is_empty()
+            && self.user_agent.is_none()
+            && self.proxy.is_none()
+            && self.proxy_credentials.is_none()
+            && self.credentials.is_none()
+            && self.cookies.is_empty()
+            && self.tls.is_empty()
+    }
+
+    fn apply(&self, source: &gst::Element) {
+        self.tls.apply(source);
+        // Only HTTP sources know these properties.
+        if !source.has_property("extra-headers", None) {
+            return;
+        }
+        if !self.headers.is_empty() {
+            let mut headers = gst::Structure::new_empty("extra-headers");
+            for (name, value) in self.headers.iter() {
+                headers.set(name.as_str(), value.as_str());
+            }
+            source.set_property("extra-headers", &headers);
+        }
+        if let Some(user_agent) = self.user_agent.as_ref() {
+            source.set_property("user-agent", user_agent);
+        }
+        if let Some(proxy) = self.proxy.as_ref() {
+            source.set_property("proxy", proxy);
+        }
+        if let Some((id, password)) = self.proxy_credentials.as_ref() {
+            source.set_property("proxy-id", id);
+            source.set_property("proxy-pw", password);
+        }
+        if let Some((id, password)) = self.credentials.as_ref() {
+            source.set_property("user-id", id);
+            source.set_property("user-pw", password);
+        }
+        if !self.cookies.is_empty() {
+            source.set_property("cookies", self.cookies.clone());
+        }
+    }
+}
+
+/// Counters updated from the appsink callbacks.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PlayerStats {
+    /// Wall time spent inside the video and audio sample callbacks, minus
+    /// waiting on a `QueuePolicy::Block` queue. This is the latency the
+    /// player adds to the streaming threads, not their CPU time: decoding
+    /// is not included, and time the thread was descheduled is.
+    pub callback_time: Duration,
+    pub video_samples: u64,
+    pub audio_samples: u64,
+    /// Decoded frames discarded by the queue policy, or skipped on the
+    /// Bevy side because a newer frame was already due.
+    pub dropped_frames: u64,
+    /// Time the decoder spent waiting on a full `QueuePolicy::Block` queue.
+    pub blocked_time: Duration,
+    /// Frames discarded or held back because the queue reached its byte
+    /// limit; also counted in `dropped_frames` when discarded.
+    pub memory_capped_frames: u64,
+}
+
+/// Download counters of the source, for a connection quality indicator.
+/// Counted for every source read through `souphttpsrc` or `uridecodebin`;
+/// adaptive streams only count what their source element fetches itself.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NetworkStats {
+    /// Bytes the source element has delivered.
+    pub bytes_downloaded: u64,
+    /// Ingest rate in bits per second over the last second or so; `None`
+    /// until measured.
+    pub bitrate: Option<u64>,
+    /// Times the stream ran dry and had to buffer again after it had filled
+    /// its buffer.
+    pub rebuffers: u32,
+    /// Last buffering percentage the source reported.
+    pub buffering_percent: Option<i32>,
+    window_start: Option<Instant>,
+    window_bytes: u64,
+}
+
+impl NetworkStats {
+    fn record_bytes(&mut self, bytes: u64) {
+        let now = Instant::now();
+        self.bytes_downloaded += bytes;
+        self.window_bytes += bytes;
+        let start = *self.window_start.get_or_insert(now);
+        let elapsed = now.duration_since(start);
+        if elapsed >= Duration::from_secs(1) {
+            self.bitrate = Some((self.window_bytes as f64 * 8.0 / elapsed.as_secs_f64()) as u64);
+            self.window_start = Some(now);
+            self.window_bytes = 0;
+        }
+    }
+
+    fn record_buffering(&mut self, percent: i32) {
+        if self.buffering_percent == Some(100) && percent < 100 {
+            self.rebuffers += 1;
+        }
+        self.buffering_percent = Some(percent);
+    }
+}
+
+/// Counts the bytes `source` pushes into `stats`.
+fn count_source_bytes(source: &gst::Element, stats: Arc<Mutex<NetworkStats>>) {
+    let Some(pad) = source.static_pad("src") else {
+        return;
+    };
+    pad.add_probe(
+        gst::PadProbeType::BUFFER | gst::PadProbeType::BUFFER_LIST,
+        move |_, info| {
+            let bytes = match (info.buffer(), info.buffer_list()) {
+                (Some(buffer), _) => buffer.size(),
+                (_, Some(list)) => list.calculate_size(),
+                _ => 0,
+            };
+            if let Ok(mut stats) = stats.lock() {
+                stats.record_bytes(bytes as u64);
+            }
+            gst::PadProbeReturn::Ok
+        },
+    );
+}
+
+/// Hooks `count_source_bytes` onto the HTTP source, or the sources
+/// uridecodebin creates.
+fn watch_network_sources(pipeline: &gst::Pipeline, stats: &Arc<Mutex<NetworkStats>>) {
+    if let Some(source) = pipeline.by_name("http_source") {
+        count_source_bytes(&source, Arc::clone(stats));
+    }
+    for name in ["decodebin", "audio_decodebin"] {
+        let Some(decodebin) = pipeline.by_name(name) else {
+            continue;
+        };
+        // Only uridecodebin creates its source.
+        if !decodebin.has_property("buffer-duration", None) {
+            continue;
+        }
+        let stats = Arc::clone(stats);
+        decodebin.connect("source-setup", false, move |values| {
+            if let Ok(source) = values[1].get::<gst::Element>() {
+                count_source_bytes(&source, Arc::clone(&stats));
+            }
+            None
+        });
+    }
+}
+
+/// What happens to decoded frames that the Bevy side has not presented yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+pub enum QueuePolicy {
+    /// Keep every frame; memory grows if presentation falls behind.
+    #[default]
+    Unbounded,
+    /// Keep at most `n` frames, discarding the oldest.
+    DropOldest(usize),
+    /// Hold the decoder until fewer than `n` frames are queued.
+    Block(usize),
+    /// Keep only the newest frame, for live sources where latency matters
+    /// more than smoothness.
+    LatestOnly,
+}
+
+impl QueuePolicy {
+    /// Queue length at which the policy kicks in.
+    pub fn capacity(self) -> Option<usize> {
+        match self {
+            QueuePolicy::Unbounded => None,
+            QueuePolicy::DropOldest(n) | QueuePolicy::Block(n) => Some(n.max(1)),
+            QueuePolicy::LatestOnly => Some(1),
+        }
+    }
+
+    /// Queue length the channel is bounded to for frames of `frame_bytes`.
+    /// `Block` also turns `max_bytes` into a frame count, since it can only
+    /// wait for room, not make it.
+    fn channel_capacity(self, frame_bytes: usize, max_bytes: Option<usize>) -> Option<usize> {
+        let byte_capacity = match self {
+            QueuePolicy::Block(_) => {
+                max_bytes.map(|max_bytes| (max_bytes / frame_bytes.max(1)).max(1))
+            }
+            _ => None,
+        };
+        [self.capacity(), byte_capacity].into_iter().flatten().min()
+    }
+
+    /// Appends `frame`, returning how many frames were discarded in total
+    /// and how many of those for exceeding `max_bytes`. The newest frame is
+    /// always kept, even if it alone is over the limit. `Block` waits for
+    /// room instead of discarding.
+    fn push(self, queue: &FrameQueue, frame: VideoInfo, max_bytes: Option<usize>) -> (u64, u64) {
+        if let QueuePolicy::Block(_) = self {
+            queue.push(frame);
+            return (0, 0);
+        }
+        let dropped = queue.push_dropping_oldest(frame);
+        let mut capped = 0;
+        if let Some(max_bytes) = max_bytes {
+            while queue.bytes() > max_bytes && queue.len() > 1 && queue.pop().is_some() {
+                capped += 1;
+            }
+        }
+        (dropped + capped, capped)
+    }
+}
+
+fn frame_channel(capacity: usize) -> (Sender<VideoInfo>, Receiver<VideoInfo>) {
+    if capacity == usize::MAX {
+        crossbeam_channel::unbounded()
+    } else {
+        crossbeam_channel::bounded(capacity)
+    }
+}
+
+/// Decoded frames on their way from the appsink callback to the Bevy side,
+/// over a lock-free channel bounded by the `QueuePolicy`. The producer
+/// resizes the channel when the policy changes while playing; the lock is
+/// only written then and when the queue is interrupted.
+pub struct FrameQueue {
+    channel: RwLock<(Sender<VideoInfo>, Receiver<VideoInfo>)>,
+    /// `usize::MAX` while unbounded.
+    capacity: AtomicUsize,
+    bytes: AtomicUsize,
+    closed: AtomicBool,
+}
+
+impl FrameQueue {
+    fn new() -> Self {
+        FrameQueue {
+            channel: RwLock::new(frame_channel(usize::MAX)),
+            capacity: AtomicUsize::new(usize::MAX),
+            bytes: AtomicUsize::new(0),
+            closed: AtomicBool::new(false),
+        }
+    }
+
+    fn sender(&self) -> Sender<VideoInfo> {
+        self.channel.read().expect("frame queue error").0.clone()
+    }
+
+    fn receiver(&self) -> Receiver<VideoInfo> {
+        self.channel.read().expect("frame queue error").1.clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.receiver().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.receiver().is_empty()
+    }
+
+    /// Size of the queued frames' data.
+    pub fn bytes(&self) -> usize {
+        self.bytes.load(Ordering::SeqCst)
+    }
+
+    /// Takes the oldest frame.
+    pub fn pop(&self) -> Option<VideoInfo> {
+        self.take(&self.receiver())
+    }
+
+    fn take(&self, receiver: &Receiver<VideoInfo>) -> Option<VideoInfo> {
+        let frame = receiver.try_recv().ok()?;
+        self.bytes.fetch_sub(frame.data.len(), Ordering::SeqCst);
+        Some(frame)
+    }
+
+    /// Takes the newest frame, discarding the older ones, and returns it
+    /// with how many were discarded. The appsink only hands frames over
+    /// once they are due, so everything queued is due by now.
+    pub fn pop_newest(&self) -> (Option<VideoInfo>, u64) {
+        let receiver = self.receiver();
+        let mut newest = None;
+        let mut skipped = 0;
+        while let Some(frame) = self.take(&receiver) {
+            if newest.replace(frame).is_some() {
+                skipped += 1;
+            }
+        }
+        (newest, skipped)
+    }
+
+    pub fn clear(&self) {
+        while self.pop().is_some() {}
+    }
+
+    /// Appends `frame`, waiting for room while the queue is full. The frame
+    /// is discarded if the queue is interrupted or closed meanwhile.
+    fn push(&self, frame: VideoInfo) {
+        let frame_bytes = frame.data.len();
+        // Counted first so a concurrent `pop` never underflows.
+        self.bytes.fetch_add(frame_bytes, Ordering::SeqCst);
+        // Taken before checking `closed`, so a `set_closed` in between
+        // replaces this sender's channel and wakes the send.
+        let sender = self.sender();
+        if self.closed.load(Ordering::SeqCst) || sender.send(frame).is_err() {
+            self.bytes.fetch_sub(frame_bytes, Ordering::SeqCst);
+        }
+    }
+
+    /// Appends `frame`, discarding the oldest frames while the queue is
+    /// full. Returns how many were discarded.
+    fn push_dropping_oldest(&self, frame: VideoInfo) -> u64 {
+        let frame_bytes = frame.data.len();
+        self.bytes.fetch_add(frame_bytes, Ordering::SeqCst);
+        let (sender, receiver) = self.channel.read().expect("frame queue error").clone();
+        let mut frame = frame;
+        let mut dropped = 0;
+        loop {
+            match sender.try_send(frame) {
+                Ok(()) => return dropped,
+                Err(TrySendError::Full(rejected)) => {
+                    frame = rejected;
+                    if self.take(&receiver).is_some() {
+                        dropped += 1;
+                    }
+                }
+                Err(TrySendError::Disconnected(_)) => {
+                    self.bytes.fetch_sub(frame_bytes, Ordering::SeqCst);
+                    return dropped;
+                }
+            }
+        }
+    }
+
+    /// Bounds the queue to `capacity` frames, `None` for no bound, keeping
+    /// the newest queued frames that fit. Returns how many were discarded.
+    fn set_capacity(&self, capacity: Option<usize>) -> u64 {
+        let capacity = capacity.unwrap_or(usize::MAX);
+        if self.capacity.swap(capacity, Ordering::SeqCst) == capacity {
+            return 0;
+        }
+        self.replace_channel(capacity, capacity)
+    }
+
+    /// Moves the newest `keep` frames to a new channel of `capacity`. A
+    /// producer blocked on the old channel wakes up once its receiver is
+    /// dropped.
+    fn replace_channel(&self, capacity: usize, keep: usize) -> u64 {
+        let (sender, receiver) = frame_channel(capacity);
+        let (_, old) = std::mem::replace(
+            &mut *self.channel.write().expect("frame queue error"),
+            (sender.clone(), receiver),
+        );
+        let frames: Vec<VideoInfo> = old.try_iter().collect();
+        drop(old);
+        let skip = frames.len().saturating_sub(keep);
+        let mut dropped = 0;
+        for (index, frame) in frames.into_iter().enumerate() {
+            let frame_bytes = frame.data.len();
+            if index < skip || sender.try_send(frame).is_err() {
+                self.bytes.fetch_sub(frame_bytes, Ordering::SeqCst);
+                dropped += 1;
+            }
+        }
+        dropped
+    }
+
+    /// Discards the queued frames and wakes a producer waiting for room.
+    fn interrupt(&self) {
+        self.replace_channel(self.capacity.load(Ordering::SeqCst), 0);
+    }
+
+    /// While closed, frames are discarded instead of waited on, so the
+    /// pipeline can shut down without the Bevy side draining the queue.
+    fn set_closed(&self, closed: bool) {
+        self.closed.store(closed, Ordering::SeqCst);
+        if closed {
+            self.interrupt();
+        }
+    }
+}
+
+/// Where a seek lands. Keyframe seeks are fast enough for scrubbing;
+/// accurate ones decode from the previous keyframe up to the exact frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Reflect)]
+pub enum SeekPrecision {
+    /// The keyframe nearest the position.
+    #[default]
+    Keyframe,
+    /// The keyframe at or before the position, so nothing after it is
+    /// skipped.
+    KeyframeBefore,
+    /// The keyframe at or after the position, so nothing before it is
+    /// shown.
+    KeyframeAfter,
+    /// Exactly the position, e.g. to resume a cutscene frame-accurately.
+    Accurate,
+}
+
+impl SeekPrecision {
+    fn flags(self) -> gst::SeekFlags {
+        match self {
+            SeekPrecision::Keyframe => gst::SeekFlags::KEY_UNIT | gst::SeekFlags::SNAP_NEAREST,
+            SeekPrecision::KeyframeBefore => gst::SeekFlags::KEY_UNIT | gst::SeekFlags::SNAP_BEFORE,
+            SeekPrecision::KeyframeAfter => gst::SeekFlags::KEY_UNIT | gst::SeekFlags::SNAP_AFTER,
+            SeekPrecision::Accurate => gst::SeekFlags::ACCURATE,
+        }
+    }
+}
+
+/// Playback rate beyond which, forwards or backwards, only keyframes are
+/// decoded.
+pub const TRICK_MODE_RATE: f64 = 2.0;
+
+/// Flags that make decoders skip to keyframes and drop audio when `rate`
+/// is too fast to decode every frame.
+fn trick_mode_flags(rate: f64) -> gst::SeekFlags {
+    if rate.abs() > TRICK_MODE_RATE {
+        gst::SeekFlags::TRICKMODE
+            | gst::SeekFlags::TRICKMODE_KEY_UNITS
+            | gst::SeekFlags::TRICKMODE_NO_AUDIO
+    } else {
+        gst::SeekFlags::empty()
+    }
+}
+
+/// Bus messages the bus handler hands over to the Bevy side.
+pub enum PlayerMessage {
+    Buffering(i32),
+    Error(String),
+    /// A GStreamer plugin the source needs isn't installed.
+    MissingPlugin {
+        name: String,
+        install_hint: String,
+    },
+    Eos,
+    /// A segment started by `play_segment` reached its end.
+    SegmentDone,
+    /// Any bus message, sent only when raw messages are enabled.
+    Bus(gst::Message),
+}
+
+/// Chapter marker from the container's table of contents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VideoChapter {
+    pub title: Option<String>,
+    pub start: Duration,
+    pub stop: Option<Duration>,
+}
+
+/// Flattens the chapters of every edition in `entries`, nested chapters
+/// included, sorted by start time.
+fn collect_chapters(entries: Vec<gst::TocEntry>, chapters: &mut Vec<VideoChapter>) {
+    for entry in entries {
+        if entry.entry_type() == gst::TocEntryType::Chapter {
+            if let Some((start, stop)) = entry.start_stop_times() {
+                let title = entry
+                    .tags()
+                    .and_then(|tags| tags.get::<gst::tags::Title>())
+                    .map(|title| title.get().to_string());
+                chapters.push(VideoChapter {
+                    title,
+                    start: Duration::from_nanos(start.max(0) as u64),
+                    stop: (stop >= 0).then(|| Duration::from_nanos(stop as u64)),
+                });
+            }
+        }
+        collect_chapters(entry.sub_entries(), chapters);
+    }
+}
+
+/// Buffering limits of a pipeline. Live streams want them small to keep
+/// latency down, 4K files larger to ride out decoding spikes.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct PipelineTuning {
+    /// Decoded frames the video sink holds before it blocks the decoder.
+    pub sink_buffers: u32,
+    /// Put a `queue` with these limits in front of the video and audio
+    /// conversion, so they run on their own threads; `None` converts on
+    /// the decoder's thread.
+    pub queue: Option<QueueLimits>,
+    /// How much of a network stream uridecodebin buffers; `None` keeps
+    /// GStreamer's default.
+    pub buffer_duration: Option<Duration>,
+    /// Network buffer size in bytes; `None` keeps GStreamer's default.
+    pub buffer_size: Option<u32>,
+    /// Download progressive network streams, e.g. MP4 over HTTP, to a
+    /// temporary file while playing, so backwards seeks are instant and
+    /// loops don't download again. The file is removed with the pipeline.
+    /// Live and adaptive streams are never cached.
+    pub download: bool,
+}
+
+impl Default for PipelineTuning {
+    fn default() -> Self {
+        PipelineTuning {
+            sink_buffers: 100,
+            queue: None,
+            buffer_duration: None,
+            buffer_size: None,
+            download: false,
+        }
+    }
+}
+
+impl PipelineTuning {
+    /// Small buffers for live sources, trading smoothness for latency.
+    pub fn live() -> Self {
+        PipelineTuning {
+            sink_buffers: 2,
+            queue: Some(QueueLimits {
+                buffers: 2,
+                bytes: 0,
+                time: Duration::ZERO,
+            }),
+            buffer_duration: Some(Duration::from_millis(500)),
+            buffer_size: None,
+            download: false,
+        }
+    }
+
+    fn queue(&self, name: &str) -> String {
+        match self.queue {
+            Some(limits) => format!(
+                "queue name={name} max-size-buffers={} max-size-bytes={} max-size-time={} ! ",
+                limits.buffers,
+                limits.bytes,
+                limits.time.as_nanos()
+            ),
+            None => String::new(),
+        }
+    }
+
+    fn apply(&self, pipeline: &gst::Pipeline) {
+        if let Some(sink) = pipeline.by_name("video_sink") {
+            sink.set_property("max-buffers", self.sink_buffers);
+        }
+        for name in ["decodebin", "audio_decodebin"] {
+            let Some(decodebin) = pipeline.by_name(name) else {
+                continue;
+            };
+            if !decodebin.has_property("buffer-duration", None) {
+                continue;
+            }
+            if let Some(duration) = self.buffer_duration {
+                decodebin.set_property("buffer-duration", duration.as_nanos() as i64);
+            }
+            if let Some(size) = self.buffer_size {
+                decodebin.set_property("buffer-size", size.min(i32::MAX as u32) as i32);
+            }
+            decodebin.set_property("download", self.download);
+        }
+    }
+}
+
+/// Limits of a `queue` element; 0 disables a limit. The queue blocks once
+/// any limit is reached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub struct QueueLimits {
+    pub buffers: u32,
+    pub bytes: u32,
+    pub time: Duration,
+}
+
+/// Elements a pipeline is built with; they cannot be added once it runs.
+#[derive(Debug, Clone, Copy)]
+pub struct PlayerOptions {
+    /// Time-stretch audio with `scaletempo` when the rate changes so voices
+    /// keep their pitch; otherwise audio is sped up like tape.
+    pub preserve_pitch: bool,
+    /// Include the equalizer and filters driven by `set_audio_effects`.
+    pub audio_effects: bool,
+    /// Play audio through GStreamer's `autoaudiosink`, e.g. OpenSL ES on
+    /// Android, instead of handing samples to rodio or `bevy_audio`.
+    /// Ducking does not apply to it.
+    pub system_audio: bool,
+    pub tuning: PipelineTuning,
+}
+
+impl Default for PlayerOptions {
+    fn default() -> Self {
+        PlayerOptions {
+            preserve_pitch: true,
+            audio_effects: false,
+            system_audio: false,
+            tuning: PipelineTuning::default(),
+        }
+    }
+}
+
+/// Where a player's media comes from.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Reflect)]
+pub enum VideoSource {
+    /// Anything uridecodebin can open: `file://`, `http(s)://`, `rtsp://`...
+    /// Sources without a video stream, like podcasts or internet radio,
+    /// play their audio without a picture.
+    Uri(String),
+    /// Video and audio from separate URIs, e.g. DASH-style split tracks or
+    /// an external music bed, played on one clock. `audio_offset_ms` delays
+    /// the audio track, negative values make it play earlier.
+    Tracks {
+        video: String,
+        audio: String,
+        audio_offset_ms: i64,
+    },
+    /// `multipart/x-mixed-replace` MJPEG stream, as served by most cheap IP
+    /// cameras. Video only.
+    Mjpeg(String),
+    /// MPEG-TS feed over SRT, e.g. `srt://encoder.local:9000` in caller mode
+    /// or `srt://:9000?mode=listener`. `passphrase` (10 to 79 characters)
+    /// decrypts an encrypted feed; `latency_ms` trades delay for resilience
+    /// to packet loss and must match the sender's, `None` keeping srtsrc's
+    /// default of 125ms.
+    Srt {
+        uri: String,
+        passphrase: Option<String>,
+        latency_ms: Option<u32>,
+    },
+    /// WebRTC stream from a WHEP endpoint, for sub-second latency remote
+    /// feeds. Needs `whepclientsrc` from gst-plugins-rs. The sender may
+    /// change resolution at any time; see `VideoResolutionChanged`.
+    Whep(String),
+    /// WebRTC stream negotiated through a GStreamer WebRTC signalling
+    /// server (`gst-webrtc-signalling-server`), e.g. `ws://127.0.0.1:8443`.
+    /// Plays `producer_id`, or the first producer that connects. Needs
+    /// `webrtcsrc` from gst-plugins-rs.
+    WebRtc {
+        signaller_uri: String,
+        producer_id: Option<String>,
+    },
+    /// Built-in beep and flash pattern used for AV-sync calibration.
+    Calibration,
+    /// Animated GIF file path, looping forever with its own frame delays.
+    Gif(String),
+    /// Numbered image files played as video, e.g. pre-rendered frames.
+    /// `location` is a printf-style pattern like `frames/%05d.png`,
+    /// starting at index 0.
+    ImageSequence {
+        location: String,
+        framerate: u32,
+        looping: bool,
+    },
+    /// Generated 320x240 30 fps `videotestsrc` picture, for tests that
+    /// exercise the frame path without media files. `tone` adds a 440 Hz
+    /// `audiotestsrc` sine; leave it off where there is no audio device.
+    Test {
+        pattern: VideoTestPattern,
+        tone: bool,
+    },
+}
+
+/// `videotestsrc` pattern of `VideoSource::Test`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Reflect)]
+pub enum VideoTestPattern {
+    /// SMPTE color bars.
+    #[default]
+    Smpte,
+    /// Random noise; not deterministic.
+    Snow,
+    Black,
+    White,
+    /// Moving ball, to see that frames advance.
+    Ball,
+    Checkers,
+    Gradient,
+}
+
+impl VideoTestPattern {
+    fn name(self) -> &'static str {
+        match self {
+            VideoTestPattern::Smpte => "smpte",
+            VideoTestPattern::Snow => "snow",
+            VideoTestPattern::Black => "black",
+            VideoTestPattern::White => "white",
+            VideoTestPattern::Ball => "ball",
+            VideoTestPattern::Checkers => "checkers-8",
+            VideoTestPattern::Gradient => "gradient",
+        }
+    }
+}
+
+/// Sets the SRT options that must not be escaped into a launch string.
+fn configure_srt_source(
+    pipeline: &gst::Pipeline,
+    passphrase: Option<&str>,
+    latency_ms: Option<u32>,
+) {
+    let Some(source) = pipeline.by_name("srt_source") else {
+        return;
+    };
+    if let Some(passphrase) = passphrase {
+        source.set_property("passphrase", passphrase);
+    }
+    if let Some(latency_ms) = latency_ms {
+        source.set_property("latency", latency_ms.min(i32::MAX as u32) as i32);
+    }
+}
+
+/// Video and audio fragments of a `webrtcsrc`-based `source`, which
+/// decodes itself and adds a pad per stream. Without audio support no audio
+/// is negotiated, so no pad is left unlinked.
+fn webrtc_sources(mut source: String) -> (String, Option<String>) {
+    if !cfg!(feature = "audio") {
+        source += " audio-codecs=\"<>\"";
+    }
+    (source, Some("webrtc_source.".to_string()))
+}
+
+impl Default for VideoSource {
+    fn default() -> Self {
+        VideoSource::Uri(String::new())
+    }
+}
+
+impl From<&str> for VideoSource {
+    fn from(uri: &str) -> Self {
+        VideoSource::Uri(uri.to_string())
+    }
+}
+
+impl From<String> for VideoSource {
+    fn from(uri: String) -> Self {
+        VideoSource::Uri(uri)
+    }
+}
+
+impl VideoSource {
+    /// Silent test picture with `pattern`.
+    pub fn test(pattern: VideoTestPattern) -> Self {
+        VideoSource::Test {
+            pattern,
+            tone: false,
+        }
+    }
+
+    /// gst-launch fragments producing the video and, if any, the audio
+    /// stream.
+    fn launch_sources(&self) -> (String, Option<String>) {
+        match self {
+            // Without audio support only the video stream is decoded.
+            VideoSource::Uri(uri) if !cfg!(feature = "audio") => (
+                format!(
+                    "uridecodebin uri={uri} name=decodebin caps=video/x-raw \
+                    expose-all-streams=false"
+                ),
+                None,
+            ),
+            VideoSource::Uri(uri) => (
+                format!("uridecodebin uri={uri} name=decodebin"),
+                Some("decodebin.".to_string()),
+            ),
+            // Only the matching stream of each URI is exposed, so an audio
+            // track in the video file does not end up unlinked.
+            VideoSource::Tracks { video, audio, .. } => (
+                format!(
+                    "uridecodebin uri={video} name=decodebin caps=video/x-raw \
+                    expose-all-streams=false"
+                ),
+                Some(format!(
+                    "uridecodebin uri={audio} name=audio_decodebin caps=audio/x-raw \
+                    expose-all-streams=false"
+                )),
+            ),
+            // A camera that stops sending parts never errors on its own, the
+            // timeout turns the stall into an error so reconnecting kicks in.
+            VideoSource::Mjpeg(uri) => (
+                format!(
+                    "souphttpsrc location={uri} name=http_source is-live=true do-timestamp=true \
+                    timeout=10 ! multipartdemux ! jpegdec"
+                ),
+                None,
+            ),
+            // The passphrase and latency are set by `configure_srt_source`,
+            // so a secret never goes through the launch parser.
+            VideoSource::Srt { uri, .. } => {
+                let source = format!("srtsrc uri=\"{uri}\" name=srt_source");
+                if cfg!(feature = "audio") {
+                    (
+                        format!("{source} ! decodebin name=decodebin"),
+                        Some("decodebin.".to_string()),
+                    )
+                } else {
+                    (
+                        format!(
+                            "{source} ! decodebin name=decodebin caps=video/x-raw \
+                            expose-all-streams=false"
+                        ),
+                        None,
+                    )
+                }
+            }
+            VideoSource::Whep(endpoint) => webrtc_sources(format!(
+                "whepclientsrc name=webrtc_source signaller::whep-endpoint=\"{endpoint}\""
+            )),
+            VideoSource::WebRtc {
+                signaller_uri,
+                producer_id,
+            } => {
+                let producer = match producer_id {
+                    Some(id) => format!("signaller::producer-peer-id=\"{id}\""),
+                    None => "connect-to-first-producer=true".to_string(),
+                };
+                webrtc_sources(format!(
+                    "webrtcsrc name=webrtc_source signaller::uri=\"{signaller_uri}\" {producer}"
+                ))
+            }
+            VideoSource::Calibration => (
+                "appsrc name=calibration_video".to_string(),
+                Some("appsrc name=calibration_audio".to_string()),
+            ),
+            VideoSource::Gif(_) => ("appsrc name=gif_source".to_string(), None),
+            // multifilesrc does not timestamp; the framerate in the caps
+            // makes the image decoder do it.
+            VideoSource::ImageSequence {
+                location,
+                framerate,
+                looping,
+            } => {
+                let extension = location.rsplit('.').next().unwrap_or_default();
+                let media = match extension.to_ascii_lowercase().as_str() {
+                    "jpg" | "jpeg" => "image/jpeg",
+                    "webp" => "image/webp",
+                    "tif" | "tiff" => "image/tiff",
+                    _ => "image/png",
+                };
+                (
+                    format!(
+                        "multifilesrc location=\"{location}\" index=0 loop={looping} \
+                        caps=\"{media},framerate={}/1\" ! decodebin",
+                        framerate.max(&1)
+                    ),
+                    None,
+                )
+            }
+            VideoSource::Test { pattern, tone } => (
+                format!(
+                    "videotestsrc pattern={} ! video/x-raw,width=320,height=240,framerate=30/1",
+                    pattern.name()
+                ),
+                tone.then(|| "audiotestsrc wave=sine freq=440".to_string()),
+            ),
+        }
+    }
+}
+
+/// Owning handle to a pipeline. It is deliberately not `Clone`: exactly one
+/// `VideoPlayer` controls a pipeline, other code observes it through
+/// [`SharedVideoSource`].
 pub struct GstPlayer {
     pipeline: gst::Pipeline,
-    pub frame: Arc<Mutex<VecDeque<VideoInfo>>>,
-    pub previous_pts: Arc<Mutex<u64>>,
-    pub duration: u64,
+    pub frame: Arc<FrameQueue>,
+    pub messages: Arc<Mutex<VecDeque<PlayerMessage>>>,
+    pub stats: Arc<Mutex<PlayerStats>>,
+    pub network_stats: Arc<Mutex<NetworkStats>>,
+    hls: Arc<Mutex<HlsState>>,
+    pub transfer: Arc<Mutex<VideoTransfer>>,
+    pub orientation: Arc<Mutex<VideoOrientation>>,
+    /// Chapters from Matroska and MP4 tables of contents, empty when the
+    /// container has none.
+    pub chapters: Arc<Mutex<Vec<VideoChapter>>>,
+    queue_policy: Arc<Mutex<QueuePolicy>>,
+    queue_byte_limit: Arc<Mutex<Option<usize>>>,
+    frame_processor: Arc<Mutex<Option<FrameProcessor>>>,
+    subscribers: FrameSubscribers,
+    rate: Arc<Mutex<f64>>,
+    volume: Arc<Mutex<f32>>,
+    ducking: Arc<Mutex<Ducking>>,
+    /// Output created by `start`, shared so playback
+    /// control can pause it together with the pipeline.
+    #[cfg(feature = "audio")]
+    audio_output: Arc<Mutex<Option<Arc<rodio::Sink>>>>,
+    running: Arc<AtomicBool>,
+    raw_messages: Arc<AtomicBool>,
+    audio_only: Arc<AtomicBool>,
+    audio_stream: Option<VideoAudioStream>,
 }
 
 impl GstPlayer {
     pub fn new(uri: &str) -> Self {
-        gst::init().expect("Failed to initialize gstreamer");
-        let pipeline = gst::parse::launch(&format!(
-            "uridecodebin uri={uri} name=decodebin ! \
-            videoconvert ! appsink name=video_sink \
-            decodebin. ! audioconvert ! appsink name=audio_sink"
-        ))
-        .expect("Failed to create pipeline")
-        .downcast::<gst::Pipeline>()
-        .expect("Expected a gst::Pipeline");
+        GstPlayer::from_source(&VideoSource::Uri(uri.to_string()))
+    }
+
+    pub fn from_source(source: &VideoSource) -> Self {
+        GstPlayer::from_source_with(source, &PlayerOptions::default())
+    }
+
+    pub fn from_source_with(source: &VideoSource, options: &PlayerOptions) -> Self {
+        ensure_gstreamer().expect("Failed to initialize gstreamer");
+        let (video_source, audio_source) = source.launch_sources();
+        let tempo = if options.preserve_pitch {
+            "scaletempo name=audio_tempo ! audioconvert ! "
+        } else {
+            ""
+        };
+        // Filters start out as passthrough: 0 dB bands, a low-pass above
+        // Nyquist and a high-pass at 0 Hz.
+        let effects = if options.audio_effects {
+            "equalizer-10bands name=audio_eq ! \
+            audiocheblimit name=audio_low_pass mode=low-pass cutoff=100000 ! \
+            audiocheblimit name=audio_high_pass mode=high-pass cutoff=0 ! "
+        } else {
+            ""
+        };
+        let audio_sink = if options.system_audio {
+            "volume name=audio_volume ! autoaudiosink name=audio_output"
+        } else {
+            "appsink name=audio_sink"
+        };
+        let audio_queue = options.tuning.queue("audio_queue");
+        let audio_branch = audio_source
+            .filter(|_| cfg!(feature = "audio"))
+            .map(|audio_source| {
+                format!(
+                    "{audio_source} ! {audio_queue}audioconvert ! {tempo}{effects}audioresample ! \
+                    {audio_sink}"
+                )
+            })
+            .unwrap_or_default();
+        let video_queue = options.tuning.queue("video_queue");
+        let description = format!(
+            "{video_source} ! {video_queue}\
+            videoconvert name=video_convert ! videoflip name=video_flip video-direction=auto ! \
+            videoscale name=video_scale ! videorate name=video_rate ! capsfilter name=video_caps ! \
+            appsink name=video_sink {audio_branch}"
+        );
+        let mut messages = VecDeque::new();
+        let mut context = gst::ParseContext::new();
+        let pipeline = match gst::parse::launch_full(
+            &description,
+            Some(&mut context),
+            gst::ParseFlags::empty(),
+        ) {
+            Ok(pipeline) => pipeline
+                .downcast::<gst::Pipeline>()
+                .expect("Expected a gst::Pipeline"),
+            Err(err) => {
+                // An empty pipeline stands in, so the failure reaches the
+                // app as a `VideoError` instead of a panic.
+                let missing = context.missing_elements();
+                if missing.is_empty() {
+                    messages.push_back(PlayerMessage::Error(err.to_string()));
+                }
+                for name in missing {
+                    let install_hint = install_hint(&name);
+                    messages.push_back(PlayerMessage::MissingPlugin { name, install_hint });
+                }
+                gst::Pipeline::new()
+            }
+        };
+        if *source == VideoSource::Calibration {
+            attach_calibration_sources(&pipeline);
+        }
+        if let VideoSource::Gif(path) = source {
+            attach_gif_source(&pipeline, path);
+        }
+        if let VideoSource::Srt {
+            passphrase,
+            latency_ms,
+            ..
+        } = source
+        {
+            configure_srt_source(&pipeline, passphrase.as_deref(), *latency_ms);
+        }
+        if let VideoSource::Tracks {
+            audio_offset_ms, ..
+        } = source
+        {
+            // A pad offset works for both backends; `autoaudiosink` is a
+            // bin without `ts-offset`.
+            let sink_pad = ["audio_sink", "audio_output"]
+                .into_iter()
+                .find_map(|name| pipeline.by_name(name))
+                .and_then(|sink| sink.static_pad("sink"));
+            if let Some(pad) = sink_pad {
+                pad.set_offset(audio_offset_ms * 1_000_000);
+            }
+        }
+        options.tuning.apply(&pipeline);
+        let network_stats = Arc::new(Mutex::new(NetworkStats::default()));
+        watch_network_sources(&pipeline, &network_stats);
+        let hls = Arc::new(Mutex::new(HlsState::default()));
+        let audio_only = Arc::new(AtomicBool::new(false));
+        if let VideoSource::Uri(_) = source {
+            drop_unused_video_branch(&pipeline, Arc::clone(&audio_only));
+            watch_hls(&pipeline, &hls);
+        }
+        let player = GstPlayer {
+            pipeline,
+            frame: Arc::new(FrameQueue::new()),
+            messages: Arc::new(Mutex::new(messages)),
+            stats: Arc::new(Mutex::new(PlayerStats::default())),
+            network_stats,
+            hls,
+            transfer: Arc::new(Mutex::new(VideoTransfer::default())),
+            orientation: Arc::new(Mutex::new(VideoOrientation::default())),
+            chapters: Arc::new(Mutex::new(Vec::new())),
+            queue_policy: Arc::new(Mutex::new(QueuePolicy::default())),
+            queue_byte_limit: Arc::new(Mutex::new(None)),
+            frame_processor: Arc::new(Mutex::new(None)),
+            subscribers: Arc::new(Mutex::new(Vec::new())),
+            rate: Arc::new(Mutex::new(1.0)),
+            volume: Arc::new(Mutex::new(1.0)),
+            ducking: Arc::new(Mutex::new(Ducking::default())),
+            #[cfg(feature = "audio")]
+            audio_output: Arc::new(Mutex::new(None)),
+            running: Arc::new(AtomicBool::new(true)),
+            raw_messages: Arc::new(AtomicBool::new(false)),
+            audio_only,
+            audio_stream: None,
+        };
+        player.install_bus_handler();
+        player
+    }
+
+    /// Handles bus messages on whichever thread posts them, so no thread
+    /// waits on the bus. They reach the Bevy side through `messages`.
+    fn install_bus_handler(&self) {
+        let bus = self.pipeline.bus().expect("Pipeline without bus");
+        let messages = Arc::clone(&self.messages);
+        let chapters = Arc::clone(&self.chapters);
+        let network_stats = Arc::clone(&self.network_stats);
+        let raw_messages = Arc::clone(&self.raw_messages);
+        bus.set_sync_handler(move |_, msg| {
+            handle_bus_message(msg, &messages, &chapters, &network_stats, &raw_messages);
+            gst::BusSyncReply::Drop
+        });
+    }
+
+    /// Second controlling handle, for the worker that sets the pipeline up.
+    pub(crate) fn control_handle(&self) -> Self {
         GstPlayer {
-            pipeline: pipeline,
-            frame: Arc::new(Mutex::new(VecDeque::new())),
-            duration: 0,
-            previous_pts: Arc::new(Mutex::new(0)),
+            pipeline: self.pipeline.clone(),
+            frame: Arc::clone(&self.frame),
+            messages: Arc::clone(&self.messages),
+            stats: Arc::clone(&self.stats),
+            network_stats: Arc::clone(&self.network_stats),
+            hls: Arc::clone(&self.hls),
+            transfer: Arc::clone(&self.transfer),
+            orientation: Arc::clone(&self.orientation),
+            chapters: Arc::clone(&self.chapters),
+            queue_policy: Arc::clone(&self.queue_policy),
+            queue_byte_limit: Arc::clone(&self.queue_byte_limit),
+            frame_processor: Arc::clone(&self.frame_processor),
+            subscribers: Arc::clone(&self.subscribers),
+            rate: Arc::clone(&self.rate),
+            volume: Arc::clone(&self.volume),
+            ducking: Arc::clone(&self.ducking),
+            #[cfg(feature = "audio")]
+            audio_output: Arc::clone(&self.audio_output),
+            running: Arc::clone(&self.running),
+            raw_messages: Arc::clone(&self.raw_messages),
+            audio_only: Arc::clone(&self.audio_only),
+            audio_stream: self.audio_stream.clone(),
+        }
+    }
+
+    pub fn share(&self) -> SharedVideoSource {
+        SharedVideoSource {
+            player: Arc::new(self.control_handle()),
+        }
+    }
+
+    #[cfg(feature = "audio")]
+    fn with_audio_output(&self, f: impl FnOnce(&rodio::Sink)) {
+        if let Ok(output) = self.audio_output.lock() {
+            if let Some(output) = output.as_ref() {
+                f(output);
+            }
+        }
+    }
+
+    /// Linear output volume, 1.0 being unchanged. Kept across pipeline
+    /// restarts of the audio output.
+    pub fn set_volume(&self, volume: f32) {
+        if let Ok(mut current) = self.volume.lock() {
+            *current = volume;
+        }
+        #[cfg(feature = "audio")]
+        self.with_audio_output(|output| output.set_volume(volume));
+        if let Some(element) = self.pipeline.by_name("audio_volume") {
+            element.set_property("volume", volume as f64);
+        }
+    }
+
+    pub fn volume(&self) -> f32 {
+        self.volume.lock().map(|volume| *volume).unwrap_or(1.0)
+    }
+
+    /// Writes the pipeline's element graph in Graphviz dot format, with caps
+    /// and element states, for diagnosing files that do not play. Render it
+    /// with `dot -Tsvg`.
+    pub fn dump_graph(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let dot = self.pipeline.debug_to_dot_data(gst::DebugGraphDetails::ALL);
+        std::fs::write(path, dot.as_str())
+    }
+
+    /// Fades the audio to `volume` times the normal volume over `fade`,
+    /// e.g. while game dialogue plays.
+    pub fn duck_to(&self, volume: f32, fade: Duration) {
+        if let Ok(mut ducking) = self.ducking.lock() {
+            ducking.fade_to(volume.max(0.0), fade);
+        }
+    }
+
+    /// Fades back to the normal volume, as fast as the last duck.
+    pub fn unduck(&self) {
+        if let Ok(mut ducking) = self.ducking.lock() {
+            let fade = ducking.fade();
+            ducking.fade_to(1.0, fade);
         }
     }
 
+    pub fn ducking(&self) -> Ducking {
+        self.ducking
+            .lock()
+            .map(|ducking| *ducking)
+            .unwrap_or_default()
+    }
+
+    /// The stream's orientation tag. Frames arrive already rotated.
+    pub fn orientation(&self) -> VideoOrientation {
+        self.orientation
+            .lock()
+            .map(|orientation| *orientation)
+            .unwrap_or_default()
+    }
+
+    pub fn position(&self) -> Option<Duration> {
+        self.pipeline
+            .query_position::<gst::ClockTime>()
+            .map(|position| Duration::from_nanos(position.nseconds()))
+    }
+
+    /// Length of the stream; `None` for live streams and until it is known.
+    pub fn duration(&self) -> Option<Duration> {
+        self.pipeline
+            .query_duration::<gst::ClockTime>()
+            .map(|duration| Duration::from_nanos(duration.nseconds()))
+    }
+
+    pub fn network_stats(&self) -> NetworkStats {
+        self.network_stats
+            .lock()
+            .map(|stats| *stats)
+            .unwrap_or_default()
+    }
+
+    /// Quality levels of an HLS source, empty until its master playlist
+    /// has been downloaded or for other sources.
+    pub fn variants(&self) -> Vec<HlsVariant> {
+        self.hls
+            .lock()
+            .map(|hls| hls.variants())
+            .unwrap_or_default()
+    }
+
+    /// Limits the variants an HLS source switches between; can be changed
+    /// while playing.
+    pub fn set_variant_selection(&self, selection: VariantSelection) {
+        if let Ok(mut hls) = self.hls.lock() {
+            hls.set_selection(selection);
+        }
+    }
+
+    /// Flushing seek to the keyframe nearest `position`. Frames decoded
+    /// before the seek are dropped.
+    pub fn seek(&self, position: Duration) {
+        self.seek_precise(position, SeekPrecision::Keyframe);
+    }
+
+    /// Flushing seek to `position` with the given precision.
+    pub fn seek_precise(&self, position: Duration, precision: SeekPrecision) {
+        let target = gst::ClockTime::from_nseconds(position.as_nanos() as u64);
+        if let Err(err) = self.seek_with_rate(
+            self.rate(),
+            target,
+            gst::SeekFlags::FLUSH | precision.flags(),
+        ) {
+            warn!("Seek failed: {err}");
+            return;
+        }
+        self.frame.clear();
+    }
+
+    /// Seeks so playback at `rate` continues from `position`; backwards
+    /// playback runs from there to the start.
+    fn seek_with_rate(
+        &self,
+        rate: f64,
+        position: gst::ClockTime,
+        flags: gst::SeekFlags,
+    ) -> Result<(), gst::glib::BoolError> {
+        let flags = flags | trick_mode_flags(rate);
+        if rate < 0.0 {
+            self.pipeline.seek(
+                rate,
+                flags,
+                gst::SeekType::Set,
+                gst::ClockTime::ZERO,
+                gst::SeekType::Set,
+                position,
+            )
+        } else {
+            self.pipeline.seek(
+                rate,
+                flags,
+                gst::SeekType::Set,
+                position,
+                gst::SeekType::None,
+                gst::ClockTime::NONE,
+            )
+        }
+    }
+
+    pub fn chapters(&self) -> Vec<VideoChapter> {
+        self.chapters
+            .lock()
+            .map(|chapters| chapters.clone())
+            .unwrap_or_default()
+    }
+
+    /// Seeks to the start of chapter `index`. Returns false if there is no
+    /// such chapter.
+    pub fn seek_to_chapter(&self, index: usize) -> bool {
+        match self.chapters().get(index) {
+            Some(chapter) => {
+                self.seek(chapter.start);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Plays from `start` to `stop`, or the end, as a segment; backwards
+    /// when `rate` is negative. Its end posts `SegmentDone` instead of
+    /// ending the stream, and a segment queued then without `flush`
+    /// continues seamlessly.
+    pub fn play_segment(&self, start: Duration, stop: Option<Duration>, rate: f64, flush: bool) {
+        let to_clock = |time: Duration| gst::ClockTime::from_nseconds(time.as_nanos() as u64);
+        // Backwards playback starts at the stop position, so it needs one.
+        let stop = stop.map(to_clock).or_else(|| {
+            (rate < 0.0)
+                .then(|| self.pipeline.query_duration::<gst::ClockTime>())
+                .flatten()
+        });
+        let stop_type = match stop {
+            Some(_) => gst::SeekType::Set,
+            None => gst::SeekType::None,
+        };
+        let mut flags = gst::SeekFlags::SEGMENT | gst::SeekFlags::ACCURATE | trick_mode_flags(rate);
+        if flush {
+            flags |= gst::SeekFlags::FLUSH;
+        }
+        if let Err(err) = self.pipeline.seek(
+            rate,
+            flags,
+            gst::SeekType::Set,
+            to_clock(start),
+            stop_type,
+            stop,
+        ) {
+            warn!("Segment seek failed: {err}");
+            return;
+        }
+        if flush {
+            self.frame.clear();
+        }
+    }
+
+    /// Playback speed, 1.0 being normal and negative rates playing
+    /// backwards. Beyond `TRICK_MODE_RATE` either way only keyframes are
+    /// decoded and audio is skipped, so fast-forwarding at 8x doesn't max
+    /// out the decoder. Without pitch preservation the rodio output is sped
+    /// up to match.
+    pub fn set_rate(&self, rate: f64) {
+        let rate = if rate < 0.0 {
+            rate.min(-0.01)
+        } else {
+            rate.max(0.01)
+        };
+        let position = self
+            .pipeline
+            .query_position::<gst::ClockTime>()
+            .unwrap_or(gst::ClockTime::ZERO);
+        if let Err(err) = self.seek_with_rate(
+            rate,
+            position,
+            gst::SeekFlags::FLUSH | gst::SeekFlags::ACCURATE,
+        ) {
+            warn!("Rate change failed: {err}");
+            return;
+        }
+        if let Ok(mut current) = self.rate.lock() {
+            *current = rate;
+        }
+        // Trick modes and reverse playback skip audio.
+        if !self.preserves_pitch() && rate > 0.0 && rate <= TRICK_MODE_RATE {
+            #[cfg(feature = "audio")]
+            self.with_audio_output(|output| output.set_speed(rate as f32));
+        }
+    }
+
+    pub fn rate(&self) -> f64 {
+        self.rate.lock().map(|rate| *rate).unwrap_or(1.0)
+    }
+
+    /// Updates the equalizer and filters. Needs a pipeline built with
+    /// `PlayerOptions::audio_effects`; can be changed while playing.
+    pub fn set_audio_effects(&self, effects: &AudioEffects) {
+        if let Some(equalizer) = self.pipeline.by_name("audio_eq") {
+            for (band, gain) in effects.equalizer.iter().enumerate() {
+                equalizer.set_property(&format!("band{band}"), gain.clamp(-24.0, 12.0));
+            }
+        }
+        if let Some(low_pass) = self.pipeline.by_name("audio_low_pass") {
+            low_pass.set_property("cutoff", effects.low_pass.unwrap_or(100_000.0));
+        }
+        if let Some(high_pass) = self.pipeline.by_name("audio_high_pass") {
+            high_pass.set_property("cutoff", effects.high_pass.unwrap_or(0.0));
+        }
+    }
+
+    pub fn preserves_pitch(&self) -> bool {
+        self.pipeline.by_name("audio_tempo").is_some()
+    }
+
     pub fn play(&self) {
         self.pipeline
             .set_state(gst::State::Playing)
             .expect("play error");
+        #[cfg(feature = "audio")]
+        self.with_audio_output(|output| output.play());
     }
 
     pub fn pause(&self) {
+        // Drop audio rodio has queued but not played yet; the pipeline
+        // clock already counts it as rendered.
+        #[cfg(feature = "audio")]
+        self.with_audio_output(|output| output.clear());
+        if let Some(stream) = self.audio_stream.as_ref() {
+            stream.clear();
+        }
         self.pipeline
             .set_state(gst::State::Paused)
             .expect("pause error");
     }
     pub fn destroy(&self) {
+        #[cfg(feature = "audio")]
+        self.with_audio_output(|output| output.stop());
+        // Only cleared: a stopped player may be played again, and a closed
+        // stream never resumes.
+        if let Some(stream) = self.audio_stream.as_ref() {
+            stream.clear();
+        }
+        // Going to `Null` waits for the streaming thread, which may be
+        // waiting on a full `QueuePolicy::Block` queue.
+        self.frame.set_closed(true);
         self.pipeline
             .set_state(gst::State::Null)
             .expect("destroy error");
+        self.frame.set_closed(false);
     }
-    pub fn start(&mut self) {
-        let (_stream, stream_handle) = OutputStream::try_default().expect("Error");
-        let ps = rodio::Sink::try_new(&stream_handle).expect("Error");
 
-        let appsink = self
-            .pipeline
-            .by_name("video_sink")
-            .expect("Sink element not found")
+    /// Tears the pipeline down and releases its worker slot.
+    pub fn shutdown(&self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(stream) = self.audio_stream.as_ref() {
+            stream.close();
+        }
+        self.destroy();
+    }
+
+    /// Configures the HTTP source once uridecodebin creates it. Must be called
+    /// before `start`.
+    pub fn set_network_options(&self, options: &NetworkOptions) {
+        if let Some(source) = self.pipeline.by_name("http_source") {
+            options.apply(&source);
+        }
+        for name in ["decodebin", "audio_decodebin"] {
+            let Some(decodebin) = self.pipeline.by_name(name) else {
+                continue;
+            };
+            let options = options.clone();
+            decodebin.connect("source-setup", false, move |values| {
+                if let Ok(source) = values[1].get::<gst::Element>() {
+                    options.apply(&source);
+                }
+                None
+            });
+        }
+    }
+
+    /// Sets the matrix and range used for YUV to RGB conversion. Must be
+    /// called before `start`.
+    pub fn set_color_space(&self, color_space: ColorSpace) {
+        if let Some(convert) = self.pipeline.by_name("video_convert") {
+            apply_color_space(&convert, color_space);
+        }
+    }
+
+    /// Sends audio to `bevy_audio` through the returned stream instead of
+    /// a private rodio output. Must be called before `start`.
+    pub fn use_bevy_audio(&mut self) -> VideoAudioStream {
+        let stream = VideoAudioStream::new(Arc::clone(&self.volume));
+        self.audio_stream = Some(stream.clone());
+        stream
+    }
+
+    pub fn audio_stream(&self) -> Option<&VideoAudioStream> {
+        self.audio_stream.as_ref()
+    }
+
+    /// Can be changed while playing; a smaller capacity applies on the next
+    /// frame.
+    pub fn set_queue_policy(&self, policy: QueuePolicy) {
+        if let Ok(mut current) = self.queue_policy.lock() {
+            *current = policy;
+        }
+    }
+
+    /// Caps the size of the queued frames' data on top of the policy's
+    /// frame count, since a 4K RGBA frame alone takes 33MB. Over the limit
+    /// the oldest frames are discarded, or the decoder is held with
+    /// `QueuePolicy::Block`. `None` removes the cap.
+    pub fn set_queue_byte_limit(&self, max_bytes: Option<usize>) {
+        if let Ok(mut current) = self.queue_byte_limit.lock() {
+            *current = max_bytes;
+        }
+    }
+
+    /// Installs or removes the callback every decoded frame goes through.
+    pub fn set_frame_processor(&self, processor: Option<FrameProcessor>) {
+        if let Ok(mut current) = self.frame_processor.lock() {
+            *current = processor;
+        }
+    }
+
+    /// Receives a copy of every decoded frame, alongside display, e.g. for
+    /// a network relay or disk writer. Frames are dropped for a receiver
+    /// that has `capacity` frames waiting; drop the receiver to unsubscribe.
+    /// Subscriptions end with the pipeline.
+    pub fn subscribe_frames(&self, capacity: usize) -> mpsc::Receiver<Arc<VideoInfo>> {
+        let (sender, receiver) = mpsc::sync_channel(capacity.max(1));
+        if let Ok(mut subscribers) = self.subscribers.lock() {
+            subscribers.push(sender);
+        }
+        receiver
+    }
+
+    /// Also hand every bus message to the Bevy side as
+    /// `PlayerMessage::Bus`.
+    pub fn set_raw_messages(&self, enabled: bool) {
+        self.raw_messages.store(enabled, Ordering::SeqCst);
+    }
+
+    /// Shifts video presentation relative to audio; positive values show
+    /// frames later. Can be changed while playing.
+    pub fn set_av_offset(&self, offset_ns: i64) {
+        if let Some(sink) = self.pipeline.by_name("video_sink") {
+            sink.set_property("ts-offset", offset_ns);
+        }
+    }
+
+    /// Constrains the decoded frame size and rate, or restores the native
+    /// stream when both are `None`.
+    pub fn set_video_scale(&self, size: Option<(u32, u32)>, framerate: Option<i32>) {
+        let Some(capsfilter) = self.pipeline.by_name("video_caps") else {
+            return;
+        };
+        let caps = if size.is_none() && framerate.is_none() {
+            gst::Caps::new_any()
+        } else {
+            let mut builder = gst_video::VideoCapsBuilder::new();
+            if let Some((width, height)) = size {
+                builder = builder
+                    .width(width as i32)
+                    .height(height as i32)
+                    .pixel_aspect_ratio(gst::Fraction::new(1, 1));
+            }
+            if let Some(framerate) = framerate {
+                builder = builder.framerate(gst::Fraction::new(framerate, 1));
+            }
+            builder.build()
+        };
+        capsfilter.set_property("caps", &caps);
+    }
+
+    /// Whether the pipeline reached `Paused`, so playback starts at once.
+    pub fn is_prerolled(&self) -> bool {
+        self.pipeline.current_state() >= gst::State::Paused
+    }
+
+    /// The frame the pipeline prerolled on reaching `Paused`, converted like
+    /// a played one. `None` until prerolling has finished.
+    pub fn preroll_frame(&self) -> Option<VideoInfo> {
+        if !self.is_prerolled() {
+            return None;
+        }
+        let appsink = self.pipeline.by_name("video_sink")?;
+        let sample = appsink.property::<Option<gst::Sample>>("last-sample")?;
+        let mut video_info = VideoInfo::from_sample(&sample)?;
+        video_info.transfer = *self.transfer.lock().ok()?;
+        let processor = self.frame_processor.lock().ok()?.clone();
+        if let Some(processor) = processor {
+            processor(&mut video_info);
+        }
+        Some(video_info)
+    }
+
+    /// Number of decoded frames waiting to be presented and their size in bytes.
+    pub fn queued_frames(&self) -> (usize, usize) {
+        (self.frame.len(), self.frame.bytes())
+    }
+
+    /// Connects the sinks and prerolls the pipeline, then returns; bus
+    /// messages are handled as they are posted.
+    pub fn start(&mut self) {
+        // Missing when the pipeline failed to build; the error is queued.
+        let Some(appsink) = self.pipeline.by_name("video_sink") else {
+            self.running.store(false, Ordering::SeqCst);
+            return;
+        };
+        let appsink = appsink
             .downcast::<gst_app::AppSink>()
             .expect("Sink element is expected to be an appsink!");
 
         appsink.set_property("sync", true);
-        appsink.set_caps(Some(
-            &gst_video::VideoCapsBuilder::new()
-                .format(gst_video::VideoFormat::Rgbx)
-                .build(),
-        ));
-        appsink.set_max_buffers(100);
-        self.pipeline
-            .set_state(gst::State::Paused)
-            .expect("paused error");
+        appsink.set_caps(Some(&video_sink_caps()));
+        // `play` may already have been called from the main thread.
+        if self.pipeline.current_state() == gst::State::Null
+            && self.pipeline.pending_state() == gst::State::VoidPending
+        {
+            self.pipeline
+                .set_state(gst::State::Paused)
+                .expect("paused error");
+        }
+        if let Some(convert) = self.pipeline.by_name("video_convert") {
+            watch_transfer(&convert, Arc::clone(&self.transfer));
+            watch_orientation(&convert, Arc::clone(&self.orientation));
+        }
+        // A flushing seek waits for the streaming thread, which may be
+        // waiting on a full `QueuePolicy::Block` queue; the seek clears the
+        // queue afterwards anyway.
+        if let Some(pad) = appsink.static_pad("sink") {
+            let queue = Arc::clone(&self.frame);
+            pad.add_probe(gst::PadProbeType::EVENT_FLUSH, move |_, info| {
+                if info
+                    .event()
+                    .is_some_and(|event| event.type_() == gst::EventType::FlushStart)
+                {
+                    queue.interrupt();
+                }
+                gst::PadProbeReturn::Ok
+            });
+        }
         let self_frame = Arc::clone(&self.frame);
+        let video_stats = Arc::clone(&self.stats);
+        let video_transfer = Arc::clone(&self.transfer);
+        let queue_policy = Arc::clone(&self.queue_policy);
+        let queue_byte_limit = Arc::clone(&self.queue_byte_limit);
+        let frame_processor = Arc::clone(&self.frame_processor);
+        let subscribers = Arc::clone(&self.subscribers);
         appsink.set_callbacks(
             gst_app::AppSinkCallbacks::builder()
                 .new_sample(move |appsink| {
+                    let _span = info_span!("video_sample").entered();
+                    let started = Instant::now();
                     let sample = appsink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
-                    let buffer = sample.buffer().ok_or_else(|| {
+                    let mut video_info = VideoInfo::from_sample(&sample).ok_or_else(|| {
                         element_error!(
                             appsink,
                             gst::ResourceError::Failed,
-                            ("Failed to get buffer from appsink")
+                            ("Failed to map video frame from appsink")
                         );
                         gst::FlowError::Error
                     })?;
-                    let caps = sample.caps().expect("Sample without caps");
-                    let info = gst_video::VideoInfo::from_caps(caps).expect("Failed to parse caps");
-                    let frame = gst_video::VideoFrameRef::from_buffer_ref_readable(buffer, &info)
-                        .map_err(|_| {
-                        element_error!(
-                            appsink,
-                            gst::ResourceError::Failed,
-                            ("Failed to map buffer readable")
-                        );
-
-                        gst::FlowError::Error
-                    })?;
-                    let pixel_data = frame.plane_data(0).expect("Failed to get pixel data");
-                    let video_info = VideoInfo {
-                        width: frame.width(),
-                        height: frame.height(),
-                        data: pixel_data.to_vec(),
-                        pts: buffer.pts().expect("pts error").nseconds(),
-                    };
-                    self_frame
+                    video_info.transfer = *video_transfer.lock().expect("transfer error");
+                    let processor = frame_processor
                         .lock()
-                        .expect("self_frame error")
-                        .push_back(video_info);
+                        .expect("frame_processor error")
+                        .clone();
+                    if let Some(processor) = processor {
+                        processor(&mut video_info);
+                    }
+                    if let Ok(mut subscribers) = subscribers.lock() {
+                        if !subscribers.is_empty() {
+                            let frame = Arc::new(video_info.clone());
+                            subscribers.retain(|subscriber| {
+                                !matches!(
+                                    subscriber.try_send(Arc::clone(&frame)),
+                                    Err(mpsc::TrySendError::Disconnected(_))
+                                )
+                            });
+                        }
+                    }
+                    let policy = *queue_policy.lock().expect("queue_policy error");
+                    let max_bytes = *queue_byte_limit.lock().expect("queue_byte_limit error");
+                    let capacity = policy.channel_capacity(video_info.data.len(), max_bytes);
+                    let resized = self_frame.set_capacity(capacity);
+                    // Waiting because of the byte limit rather than the
+                    // frame count counts as memory capped.
+                    let held = matches!(policy, QueuePolicy::Block(_))
+                        && capacity < policy.capacity()
+                        && self_frame.len() >= capacity.unwrap_or(usize::MAX);
+                    let waiting = Instant::now();
+                    let (dropped, capped) = policy.push(&self_frame, video_info, max_bytes);
+                    let blocked = match policy {
+                        QueuePolicy::Block(_) => waiting.elapsed(),
+                        _ => Duration::ZERO,
+                    };
+                    if let Ok(mut stats) = video_stats.lock() {
+                        stats.callback_time += started.elapsed() - blocked;
+                        stats.video_samples += 1;
+                        stats.dropped_frames += dropped + resized;
+                        stats.blocked_time += blocked;
+                        stats.memory_capped_frames += capped + held as u64;
+                    }
                     Ok(gst::FlowSuccess::Ok)
                 })
                 .build(),
         );
-        let audio_sink = self
-            .pipeline
-            .by_name("audio_sink")
-            .expect("Audio sink element not found")
-            .downcast::<gst_app::AppSink>()
-            .expect("Audio sink element is expected to be an appsink!");
-        let bus = self.pipeline.bus().expect("Pipeline without bus");
-        audio_sink.set_caps(Some(
-            &gst_audio::AudioCapsBuilder::new()
-                .format(gst_audio::AudioFormat::F32le)
-                .build(),
-        ));
-        audio_sink.set_callbacks(
-            gst_app::AppSinkCallbacks::builder()
-                .new_sample(move |audio_sink| {
-                    let sample = audio_sink
-                        .pull_sample()
-                        .map_err(|_| gst::FlowError::Eos)
-                        .expect("Error");
-                    let buffer = sample
-                        .buffer()
-                        .ok_or_else(|| {
+        // Video-only sources have no audio branch.
+        #[cfg(feature = "audio")]
+        if let Some(audio_sink) = self.pipeline.by_name("audio_sink") {
+            let audio_sink = audio_sink
+                .downcast::<gst_app::AppSink>()
+                .expect("Audio sink element is expected to be an appsink!");
+            // With the bevy_audio backend samples go to `audio_stream` instead
+            // of a private output.
+            let ps = match self.audio_stream {
+                Some(_) => None,
+                None => rodio_output()
+                    .and_then(|handle| rodio::Sink::try_new(&handle).ok())
+                    .map(|sink| {
+                        let sink = Arc::new(sink);
+                        sink.set_volume(self.volume());
+                        *self.audio_output.lock().expect("audio_output error") =
+                            Some(Arc::clone(&sink));
+                        sink
+                    }),
+            };
+            // Whatever the decoder produces (S16, planar, 5.1...), audioconvert
+            // and audioresample turn it into interleaved F32LE. Surround is
+            // downmixed to stereo, since rodio drops channels it cannot map.
+            let caps = gst_audio::AudioCapsBuilder::new_interleaved()
+                .format(gst_audio::AudioFormat::F32le);
+            let caps = match self.audio_stream {
+                Some(_) => caps
+                    .rate(STREAM_RATE as i32)
+                    .channels(STREAM_CHANNELS as i32),
+                None => caps.channels_range(1..=2),
+            };
+            audio_sink.set_caps(Some(&caps.build()));
+            let audio_stream = self.audio_stream.clone();
+            let audio_stats = Arc::clone(&self.stats);
+            let ducking = Arc::clone(&self.ducking);
+            audio_sink.set_callbacks(
+                gst_app::AppSinkCallbacks::builder()
+                    .new_sample(move |audio_sink| {
+                        let _span = info_span!("audio_sample").entered();
+                        let started = Instant::now();
+                        let sample = audio_sink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+                        // Samples still in flight when a pause was requested
+                        // would otherwise play ahead of the frozen video.
+                        if audio_sink.current_state() != gst::State::Playing {
+                            return Ok(gst::FlowSuccess::Ok);
+                        }
+                        let buffer = sample.buffer().ok_or_else(|| {
                             element_error!(
                                 audio_sink,
                                 gst::ResourceError::Failed,
                                 ("Failed to get buffer from appsink")
                             );
                             gst::FlowError::Error
-                        })
-                        .expect("Error");
-
-                    let caps = sample.caps().expect("Sample without caps");
-                    let info = gst_audio::AudioInfo::from_caps(caps).expect("Failed to parse caps");
-                    let map: gstreamer::BufferMap<gstreamer::buffer::Readable> =
-                        buffer.map_readable().map_err(|_| {
-                            element_error!(
-                                appsink,
-                                gst::ResourceError::Failed,
-                                ("Failed to map buffer readable")
-                            );
-                            gst::FlowError::Error
                         })?;
-                    let u8_data: &[u8] = map.as_slice();
-                    let mut f32_data = vec![0f32; u8_data.len() / 4];
-                    LittleEndian::read_f32_into(u8_data, &mut f32_data);
-                    let ch = info.channels() as u16;
-                    let rate = info.rate();
-                    let s = rodio::buffer::SamplesBuffer::new(ch, rate, f32_data);
-                    ps.append(s);
-                    Ok(gst::FlowSuccess::Ok)
-                })
-                .build(),
-        );
-        for msg in bus.iter_timed(gst::ClockTime::NONE) {
-            use gst::MessageView;
-            match msg.view() {
-                MessageView::StateChanged(state_changed) => {
-                    if state_changed
-                        .src()
-                        .map(|s| s == &self.pipeline)
-                        .unwrap_or(false)
-                        && state_changed.current() == gst::State::Playing
-                    {
-                    } else if state_changed
-                        .src()
-                        .map(|s| s == &self.pipeline)
-                        .unwrap_or(false)
-                        && state_changed.current() == gst::State::Paused
-                    {
-                        if let Some(duration) = self.pipeline.query_duration::<gst::ClockTime>() {
-                            self.duration = duration.mseconds();
+
+                        let info = sample
+                            .caps()
+                            .and_then(|caps| gst_audio::AudioInfo::from_caps(caps).ok())
+                            .ok_or(gst::FlowError::NotNegotiated)?;
+                        let map: gstreamer::BufferMap<gstreamer::buffer::Readable> =
+                            buffer.map_readable().map_err(|_| {
+                                element_error!(
+                                    audio_sink,
+                                    gst::ResourceError::Failed,
+                                    ("Failed to map buffer readable")
+                                );
+                                gst::FlowError::Error
+                            })?;
+                        let u8_data: &[u8] = map.as_slice();
+                        let u8_data = &u8_data[..u8_data.len() - u8_data.len() % 4];
+                        let mut f32_data = vec![0f32; u8_data.len() / 4];
+                        LittleEndian::read_f32_into(u8_data, &mut f32_data);
+                        let gain = ducking
+                            .lock()
+                            .map(|ducking| ducking.gain(Instant::now()))
+                            .unwrap_or(1.0);
+                        if gain != 1.0 {
+                            f32_data.iter_mut().for_each(|sample| *sample *= gain);
+                        }
+                        let ch = info.channels() as u16;
+                        let rate = info.rate();
+                        if let Some(stream) = audio_stream.as_ref() {
+                            stream.push(&f32_data);
+                        } else if let Some(ps) = ps.as_ref() {
+                            ps.append(rodio::buffer::SamplesBuffer::new(ch, rate, f32_data));
+                        }
+                        if let Ok(mut stats) = audio_stats.lock() {
+                            stats.callback_time += started.elapsed();
+                            stats.audio_samples += 1;
+                        }
+                        Ok(gst::FlowSuccess::Ok)
+                    })
+                    .build(),
+            );
+        }
+    }
+
+    pub(crate) fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    /// Set once the source turned out to have no video stream; it plays its
+    /// audio and never delivers frames.
+    pub fn is_audio_only(&self) -> bool {
+        self.audio_only.load(Ordering::SeqCst)
+    }
+}
+
+/// Elements between the decoder and `video_sink`.
+const VIDEO_BRANCH: [&str; 7] = [
+    "video_queue",
+    "video_convert",
+    "video_flip",
+    "video_scale",
+    "video_rate",
+    "video_caps",
+    "video_sink",
+];
+
+/// Removes the video branch once `decodebin` has exposed all its streams
+/// without a video one, e.g. for a podcast. The unlinked sink would
+/// otherwise wait for a frame forever and keep the pipeline from
+/// prerolling.
+fn drop_unused_video_branch(pipeline: &gst::Pipeline, audio_only: Arc<AtomicBool>) {
+    let Some(decodebin) = pipeline.by_name("decodebin") else {
+        return;
+    };
+    let has_video = Arc::new(AtomicBool::new(false));
+    let video_seen = Arc::clone(&has_video);
+    decodebin.connect_pad_added(move |_, pad| {
+        let caps = pad.current_caps().unwrap_or_else(|| pad.query_caps(None));
+        let is_video = caps
+            .structure(0)
+            .is_some_and(|structure| structure.name().starts_with("video/"));
+        if is_video {
+            video_seen.store(true, Ordering::SeqCst);
+        }
+    });
+    let pipeline = pipeline.downgrade();
+    decodebin.connect_no_more_pads(move |_| {
+        if has_video.load(Ordering::SeqCst) {
+            return;
+        }
+        let Some(pipeline) = pipeline.upgrade() else {
+            return;
+        };
+        audio_only.store(true, Ordering::SeqCst);
+        for name in VIDEO_BRANCH {
+            if let Some(element) = pipeline.by_name(name) {
+                let _ = element.set_state(gst::State::Null);
+                let _ = pipeline.remove(&element);
+            }
+        }
+    });
+}
+
+/// Handle to the default output device. The `!Send` stream is opened once,
+/// on a thread that keeps it alive for the rest of the process and is
+/// shared by every player. `None` without an output device.
+#[cfg(feature = "audio")]
+fn rodio_output() -> Option<OutputStreamHandle> {
+    static OUTPUT: OnceLock<Option<OutputStreamHandle>> = OnceLock::new();
+    OUTPUT
+        .get_or_init(|| {
+            let (sender, receiver) = mpsc::channel();
+            thread::Builder::new()
+                .name("video-audio-output".to_string())
+                .spawn(move || match OutputStream::try_default() {
+                    Ok((_stream, handle)) => {
+                        let _ = sender.send(Some(handle));
+                        loop {
+                            thread::park();
                         }
                     }
-                }
-                MessageView::Eos(..) => {
-                    println!("EOS");
-                    break;
-                }
-                MessageView::Error(err) => {
-                    eprintln!(
-                        "Error from {:?}: {} ({:?})",
-                        err.src().map(|s| s.path_string()),
-                        err.error(),
-                        err.debug()
-                    );
-                    break;
-                }
-                _ => (),
+                    Err(err) => {
+                        warn!("No audio output for video: {err}");
+                        let _ = sender.send(None);
+                    }
+                })
+                .ok()?;
+            receiver.recv().ok().flatten()
+        })
+        .clone()
+}
+
+fn handle_bus_message(
+    msg: &gst::Message,
+    messages: &Mutex<VecDeque<PlayerMessage>>,
+    chapters: &Mutex<Vec<VideoChapter>>,
+    network_stats: &Mutex<NetworkStats>,
+    raw_messages: &AtomicBool,
+) {
+    let push = |message| {
+        if let Ok(mut messages) = messages.lock() {
+            messages.push_back(message);
+        }
+    };
+    if raw_messages.load(Ordering::SeqCst) {
+        push(PlayerMessage::Bus(msg.clone()));
+    }
+    use gst::MessageView;
+    match msg.view() {
+        MessageView::Buffering(buffering) => {
+            if let Ok(mut stats) = network_stats.lock() {
+                stats.record_buffering(buffering.percent());
+            }
+            push(PlayerMessage::Buffering(buffering.percent()));
+        }
+        MessageView::Eos(..) => {
+            debug!("EOS");
+            push(PlayerMessage::Eos);
+        }
+        MessageView::Toc(toc) => {
+            let (toc, _) = toc.toc();
+            let mut collected = Vec::new();
+            collect_chapters(toc.entries(), &mut collected);
+            collected.sort_by_key(|chapter| chapter.start);
+            if let Ok(mut chapters) = chapters.lock() {
+                *chapters = collected;
+            }
+        }
+        MessageView::SegmentDone(..) => push(PlayerMessage::SegmentDone),
+        MessageView::Element(element) => {
+            if let Some((name, install_hint)) = element.structure().and_then(parse_missing_plugin) {
+                push(PlayerMessage::MissingPlugin { name, install_hint });
             }
         }
+        // decodebin posts a `missing-plugin` message before failing, which
+        // says what is missing.
+        MessageView::Error(err) if is_missing_plugin_error(&err.error()) => {
+            let reported = messages.lock().is_ok_and(|messages| {
+                messages
+                    .iter()
+                    .any(|message| matches!(message, PlayerMessage::MissingPlugin { .. }))
+            });
+            if !reported {
+                let name = err
+                    .src()
+                    .map(|src| src.name().to_string())
+                    .unwrap_or_default();
+                let install_hint = install_hint(&name);
+                push(PlayerMessage::MissingPlugin { name, install_hint });
+            }
+        }
+        MessageView::Error(err) => {
+            let message = format!(
+                "Error from {:?}: {} ({:?})",
+                err.src().map(|s| s.path_string()),
+                err.error(),
+                err.debug()
+            );
+            error!("{message}");
+            push(PlayerMessage::Error(message));
+        }
+        _ => (),
+    }
+}
+
+/// Read-only view of a pipeline owned by a `VideoPlayer`. It can be cloned
+/// freely and outlive the owner, but cannot change playback state; once the
+/// owner is gone the pipeline simply reports `Null`.
+#[derive(Clone)]
+pub struct SharedVideoSource {
+    player: Arc<GstPlayer>,
+}
+
+impl SharedVideoSource {
+    pub fn state(&self) -> gst::State {
+        self.player.pipeline.current_state()
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.state() == gst::State::Playing
+    }
+
+    pub fn position(&self) -> Option<Duration> {
+        self.player.position()
+    }
+
+    pub fn duration(&self) -> Option<Duration> {
+        self.player.duration()
+    }
+
+    pub fn queued_frames(&self) -> (usize, usize) {
+        self.player.queued_frames()
+    }
+
+    pub fn stats(&self) -> PlayerStats {
+        self.player
+            .stats
+            .lock()
+            .map(|stats| *stats)
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME_BYTES: usize = 16;
+
+    fn frame(pts: u64) -> VideoInfo {
+        VideoInfo {
+            height: 2,
+            width: 2,
+            data: vec![0; FRAME_BYTES],
+            pts,
+            has_alpha: false,
+            format: gst_video::VideoFormat::Rgba,
+            transfer: VideoTransfer::Sdr,
+        }
+    }
+
+    /// Pushes like the appsink callback does: resize, then push.
+    fn push(queue: &FrameQueue, policy: QueuePolicy, pts: u64, max_bytes: Option<usize>) {
+        queue.set_capacity(policy.channel_capacity(FRAME_BYTES, max_bytes));
+        policy.push(queue, frame(pts), max_bytes);
+    }
+
+    fn drain(queue: &FrameQueue) -> Vec<u64> {
+        std::iter::from_fn(|| queue.pop())
+            .map(|frame| frame.pts)
+            .collect()
+    }
+
+    #[test]
+    fn byte_count_returns_to_zero() {
+        let queue = FrameQueue::new();
+        for pts in 0..3 {
+            push(&queue, QueuePolicy::Unbounded, pts, None);
+        }
+        assert_eq!(queue.bytes(), 3 * FRAME_BYTES);
+        queue.clear();
+        assert_eq!(queue.bytes(), 0);
+
+        for pts in 0..3 {
+            push(&queue, QueuePolicy::Unbounded, pts, None);
+        }
+        queue.interrupt();
+        assert_eq!(queue.bytes(), 0);
+        assert!(queue.is_empty());
+
+        for pts in 0..3 {
+            push(&queue, QueuePolicy::Unbounded, pts, None);
+        }
+        assert_eq!(queue.set_capacity(Some(1)), 2);
+        assert_eq!(queue.bytes(), FRAME_BYTES);
+        assert_eq!(drain(&queue), [2]);
+        assert_eq!(queue.bytes(), 0);
+    }
+
+    #[test]
+    fn full_queues_keep_the_newest_frames() {
+        let queue = FrameQueue::new();
+        for pts in 0..5 {
+            push(&queue, QueuePolicy::DropOldest(2), pts, None);
+        }
+        assert_eq!(drain(&queue), [3, 4]);
+
+        for pts in 0..5 {
+            push(&queue, QueuePolicy::LatestOnly, pts, None);
+        }
+        assert_eq!(drain(&queue), [4]);
+
+        for pts in 0..5 {
+            push(&queue, QueuePolicy::Unbounded, pts, Some(3 * FRAME_BYTES));
+        }
+        assert_eq!(drain(&queue), [2, 3, 4]);
+        assert_eq!(queue.bytes(), 0);
+    }
+
+    #[test]
+    fn closing_wakes_a_blocked_producer() {
+        let queue = Arc::new(FrameQueue::new());
+        push(&queue, QueuePolicy::Block(1), 0, None);
+        let producer = Arc::clone(&queue);
+        let (done, finished) = crossbeam_channel::bounded(1);
+        std::thread::spawn(move || {
+            push(&producer, QueuePolicy::Block(1), 1, None);
+            let _ = done.send(());
+        });
+        std::thread::sleep(Duration::from_millis(50));
+        assert!(finished.try_recv().is_err(), "producer did not wait");
+        queue.set_closed(true);
+        assert!(finished.recv_timeout(Duration::from_secs(5)).is_ok());
+        assert!(queue.is_empty());
+        assert_eq!(queue.bytes(), 0);
+    }
+
+    /// `(dropped, capped)` reported for each of `count` pushes.
+    fn push_counts(policy: QueuePolicy, count: u64, max_bytes: Option<usize>) -> Vec<(u64, u64)> {
+        let queue = FrameQueue::new();
+        (0..count)
+            .map(|pts| {
+                queue.set_capacity(policy.channel_capacity(FRAME_BYTES, max_bytes));
+                policy.push(&queue, frame(pts), max_bytes)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn policies_report_dropped_and_capped_frames() {
+        assert_eq!(push_counts(QueuePolicy::Unbounded, 3, None), [(0, 0); 3]);
+        assert_eq!(
+            push_counts(QueuePolicy::DropOldest(2), 4, None),
+            [(0, 0), (0, 0), (1, 0), (1, 0)]
+        );
+        assert_eq!(
+            push_counts(QueuePolicy::LatestOnly, 3, None),
+            [(0, 0), (1, 0), (1, 0)]
+        );
+        assert_eq!(push_counts(QueuePolicy::Block(4), 3, None), [(0, 0); 3]);
+    }
+
+    #[test]
+    fn byte_limit_drops_count_as_capped() {
+        let max_bytes = Some(2 * FRAME_BYTES);
+        assert_eq!(
+            push_counts(QueuePolicy::Unbounded, 3, max_bytes),
+            [(0, 0), (0, 0), (1, 1)]
+        );
+        // The frame count binds before the byte limit here.
+        assert_eq!(
+            push_counts(QueuePolicy::DropOldest(1), 2, max_bytes),
+            [(0, 0), (1, 0)]
+        );
+        // `Block` turns the limit into its capacity and never discards.
+        assert_eq!(
+            QueuePolicy::Block(4).channel_capacity(FRAME_BYTES, max_bytes),
+            Some(2)
+        );
+        assert_eq!(
+            push_counts(QueuePolicy::Block(4), 2, max_bytes),
+            [(0, 0); 2]
+        );
+    }
+
+    #[test]
+    fn block_capacity_covers_frames_over_the_byte_limit() {
+        let block = QueuePolicy::Block(4);
+        assert_eq!(block.channel_capacity(100, Some(50)), Some(1));
+        assert_eq!(block.channel_capacity(100, Some(250)), Some(2));
+        assert_eq!(block.channel_capacity(100, Some(1000)), Some(4));
+        assert_eq!(block.channel_capacity(0, Some(50)), Some(4));
+        assert_eq!(
+            QueuePolicy::DropOldest(4).channel_capacity(100, Some(50)),
+            Some(4)
+        );
+        assert_eq!(QueuePolicy::Unbounded.channel_capacity(100, Some(50)), None);
     }
 }
@@ -0,0 +1,258 @@
+use std::{
+    any::Any,
+    panic::{self, AssertUnwindSafe},
+    thread,
+    time::{Duration, Instant},
+};
+
+use bevy::{
+    app::AppExit,
+    prelude::*,
+    tasks::{block_on, AsyncComputeTaskPool, Task, TaskPool},
+};
+
+use crate::{
+    plugin::{VideoPlayer, VideoPluginConfig},
+    video::GstPlayer,
+};
+
+/// One player's pipeline, set up by a task on the `AsyncComputeTaskPool`.
+/// The worker stays registered until the pipeline is shut down.
+pub struct VideoWorker {
+    pub name: String,
+    pub entity: Entity,
+    pipeline: GstPlayer,
+    setup: Option<Task<thread::Result<()>>>,
+}
+
+/// Sent when a pipeline's setup task panicked; the player will not receive
+/// new frames.
+#[derive(Event, Debug, Clone)]
+pub struct VideoWorkerPanicked {
+    pub entity: Entity,
+    pub name: String,
+    pub message: String,
+}
+
+/// Owns every pipeline started by the plugin. Setup tasks are collected when
+/// they finish, and the remaining pipelines are shut down on drop.
+#[derive(Resource)]
+pub struct VideoWorkers {
+    workers: Vec<VideoWorker>,
+    /// Upper bound on simultaneously running pipelines, unlimited by
+    /// default. Players past the limit stay in `VideoState::Init` until a
+    /// slot frees up.
+    pub max_workers: usize,
+    warned_full: bool,
+}
+
+impl Default for VideoWorkers {
+    fn default() -> Self {
+        VideoWorkers {
+            workers: Vec::new(),
+            max_workers: usize::MAX,
+            warned_full: false,
+        }
+    }
+}
+
+impl VideoWorkers {
+    pub fn len(&self) -> usize {
+        self.workers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.workers.is_empty()
+    }
+
+    pub fn has_capacity(&self) -> bool {
+        let running = self
+            .workers
+            .iter()
+            .filter(|worker| worker.pipeline.is_running())
+            .count();
+        running < self.max_workers
+    }
+
+    /// Logs the first time a player has to wait for a slot, which is
+    /// otherwise only visible as it staying in `VideoState::Init`.
+    pub(crate) fn warn_held_back(&mut self, entity: Entity) {
+        if !self.warned_full {
+            self.warned_full = true;
+            warn!(
+                "{entity:?} waits for a free pipeline slot; all {} are in use \
+                (see VideoWorkers::max_workers)",
+                self.max_workers
+            );
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &VideoWorker> {
+        self.workers.iter()
+    }
+
+    pub fn spawn(&mut self, entity: Entity, pipeline: GstPlayer) {
+        let name = format!("video-{entity:?}");
+        let mut worker_pipeline = pipeline.control_handle();
+        let setup = AsyncComputeTaskPool::get_or_init(TaskPool::default).spawn(async move {
+            panic::catch_unwind(AssertUnwindSafe(|| worker_pipeline.start()))
+        });
+        self.workers.push(VideoWorker {
+            name,
+            entity,
+            pipeline,
+            setup: Some(setup),
+        });
+    }
+
+    /// Shuts down the pipelines of `entity`'s workers; `reap` removes them.
+    pub fn shutdown(&mut self, entity: Entity) {
+        for worker in self.workers.iter().filter(|worker| worker.entity == entity) {
+            worker.pipeline.shutdown();
+        }
+    }
+
+    /// Removes the workers whose pipeline was shut down or whose setup
+    /// task panicked.
+    pub fn reap(&mut self) -> Vec<(VideoWorker, thread::Result<()>)> {
+        let mut finished = Vec::new();
+        let mut index = 0;
+        while index < self.workers.len() {
+            let worker = &mut self.workers[index];
+            let result = if worker.is_finished() {
+                worker.join()
+            } else {
+                Ok(())
+            };
+            let done = worker.setup.is_none() && !worker.pipeline.is_running();
+            if result.is_err() || done {
+                let worker = self.workers.swap_remove(index);
+                worker.pipeline.shutdown();
+                finished.push((worker, result));
+            } else {
+                index += 1;
+            }
+        }
+        finished
+    }
+
+    /// Shuts down every pipeline and waits for its setup task.
+    pub fn join_all(&mut self) -> Vec<(VideoWorker, thread::Result<()>)> {
+        for worker in self.workers.iter() {
+            worker.pipeline.shutdown();
+        }
+        self.workers
+            .drain(..)
+            .map(|mut worker| {
+                let result = worker.join();
+                (worker, result)
+            })
+            .collect()
+    }
+
+    /// Like `join_all`, but gives up on setup tasks still running after
+    /// `timeout`; those are detached and returned as not joined.
+    pub fn join_all_timeout(&mut self, timeout: Duration) -> Vec<VideoWorker> {
+        for worker in self.workers.iter() {
+            worker.pipeline.shutdown();
+        }
+        let deadline = Instant::now() + timeout;
+        while Instant::now() < deadline && self.workers.iter().any(|worker| !worker.is_finished()) {
+            thread::sleep(Duration::from_millis(10));
+        }
+        for (worker, result) in self.reap() {
+            if let Err(payload) = result {
+                error!("{} panicked: {}", worker.name, panic_message(&payload));
+            }
+        }
+        self.workers
+            .drain(..)
+            .map(|mut worker| {
+                if let Some(setup) = worker.setup.take() {
+                    setup.detach();
+                }
+                worker
+            })
+            .collect()
+    }
+}
+
+/// Dropped on the main thread, which must not wait for setup tasks: on
+/// macOS some elements, e.g. camera sources, dispatch work to the main
+/// queue while starting and would never finish.
+impl Drop for VideoWorkers {
+    fn drop(&mut self) {
+        for worker in self.join_all_timeout(Duration::ZERO) {
+            debug!("{} still starting, detaching it", worker.name);
+        }
+    }
+}
+
+impl VideoWorker {
+    fn is_finished(&self) -> bool {
+        self.setup
+            .as_ref()
+            .map(|setup| setup.is_finished())
+            .unwrap_or(true)
+    }
+
+    fn join(&mut self) -> thread::Result<()> {
+        match self.setup.take() {
+            Some(setup) => block_on(setup),
+            None => Ok(()),
+        }
+    }
+}
+
+pub fn panic_message(payload: &Box<dyn Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+pub fn reap_video_workers(
+    mut workers: ResMut<VideoWorkers>,
+    mut panic_events: EventWriter<VideoWorkerPanicked>,
+) {
+    for (worker, result) in workers.reap() {
+        if let Err(payload) = result {
+            let message = panic_message(&payload);
+            error!("{} panicked: {}", worker.name, message);
+            panic_events.send(VideoWorkerPanicked {
+                entity: worker.entity,
+                name: worker.name,
+                message,
+            });
+        }
+    }
+}
+
+/// Stops the pipelines of despawned players, or players whose `VideoPlayer`
+/// was removed.
+pub fn shutdown_removed_players(
+    mut removed: RemovedComponents<VideoPlayer>,
+    mut workers: ResMut<VideoWorkers>,
+) {
+    for entity in removed.read() {
+        workers.shutdown(entity);
+    }
+}
+
+/// Stops every pipeline when the app exits, waiting up to
+/// `VideoPluginConfig::shutdown_timeout` for setup tasks still running.
+pub fn shutdown_video_workers_on_exit(
+    mut exit: EventReader<AppExit>,
+    mut workers: ResMut<VideoWorkers>,
+    config: Res<VideoPluginConfig>,
+) {
+    if exit.read().last().is_none() {
+        return;
+    }
+    for worker in workers.join_all_timeout(config.shutdown_timeout) {
+        warn!("{} did not exit in time, detaching it", worker.name);
+    }
+}
@@ -0,0 +1,36 @@
+//! Plays a generated test picture through the plugin without a window. Run
+//! with `cargo test --features test-media`; needs GStreamer's base plugins.
+#![cfg(feature = "test-media")]
+
+use std::{thread, time::Duration};
+
+use bevy::prelude::*;
+use bevy_gst_video::{
+    headless::{headless_app, spawn_headless_player},
+    plugin::VideoPlayer,
+    video::{VideoSource, VideoTestPattern},
+};
+
+#[test]
+fn test_pattern_frames_reach_texture() {
+    let mut app = headless_app();
+    let mut video_player = VideoPlayer {
+        source: VideoSource::test(VideoTestPattern::Ball),
+        ..Default::default()
+    };
+    video_player.play();
+    let entity = spawn_headless_player(&mut app.world, video_player);
+
+    let mut frame = None;
+    for _ in 0..300 {
+        app.update();
+        let video_player = app.world.get::<VideoPlayer>(entity).unwrap();
+        frame = video_player.snapshot(app.world.resource::<Assets<Image>>());
+        if video_player.presented_pts().is_some() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    let frame = frame.expect("no frame presented");
+    assert_eq!(frame.size(), UVec2::new(320, 240));
+}
@@ -0,0 +1,39 @@
+//! Decodes every fixture from `bevy_gst_video::test_support`. Run with
+//! `cargo test --features test-media`; fixtures whose encoder or source
+//! elements are not installed are skipped.
+#![cfg(feature = "test-media")]
+
+use bevy_gst_video::test_support::{decode, FixtureError, FIXTURES};
+
+#[test]
+fn fixtures_decode() {
+    let mut failures = Vec::new();
+    for fixture in FIXTURES {
+        let path = match fixture.fetch() {
+            Ok(path) => path,
+            Err(FixtureError::MissingElement(element)) => {
+                eprintln!("skipping {}: {element} not installed", fixture.name);
+                continue;
+            }
+            Err(err) => {
+                failures.push(format!("{}: {err}", fixture.name));
+                continue;
+            }
+        };
+        match decode(&path) {
+            Ok(report) => {
+                if report.video_frames == 0 {
+                    failures.push(format!("{}: no video frames", fixture.name));
+                }
+                if report.audio_peak > 1.0 {
+                    failures.push(format!(
+                        "{}: audio peak {} out of range",
+                        fixture.name, report.audio_peak
+                    ));
+                }
+            }
+            Err(err) => failures.push(format!("{}: {err}", fixture.name)),
+        }
+    }
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}